@@ -1,8 +1,13 @@
 use crate::addon::blocks::block::Block;
+use crate::addon::diff::FileDiff;
 use crate::addon::items::item::Item;
 use crate::addon::path_resolver::AddonPathResolver;
 use crate::addon::traits::FormattedJsonSerialize;
-use std::collections::HashMap;
+use crate::vfs::archive::{write_archive, ArchiveError};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Debug)]
@@ -10,6 +15,12 @@ pub struct Addon {
     pub resolver: Box<dyn AddonPathResolver>,
     blocks: HashMap<String, Block>,
     items: HashMap<String, Item>,
+    read_only_blocks: HashSet<String>,
+    read_only_items: HashSet<String>,
+    /// Legacy items upgraded since parsing, whose resource pack half is removed on write
+    upgraded_items: HashSet<String>,
+    /// Files besides blocks and items, like generated scripts, written along with them
+    extra_files: BTreeMap<PathBuf, String>,
 }
 
 #[derive(Error, Debug)]
@@ -18,6 +29,8 @@ pub enum AddonSerError {
     JsonError(#[from] serde_json::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    ArchiveError(#[from] ArchiveError),
 }
 
 impl Addon {
@@ -26,44 +39,322 @@ impl Addon {
             resolver: Box::from(resolver),
             blocks: HashMap::new(),
             items: HashMap::new(),
+            read_only_blocks: HashSet::new(),
+            read_only_items: HashSet::new(),
+            upgraded_items: HashSet::new(),
+            extra_files: BTreeMap::new(),
         }
     }
 
     pub fn push_block(&mut self, block: Block) {
+        self.read_only_blocks.remove(&block.description.identifier);
         self.blocks
             .insert(block.description.identifier.clone(), block);
     }
     pub fn push_item(&mut self, block: Item) {
+        self.read_only_items.remove(&block.description.identifier);
         self.items
             .insert(block.description.identifier.clone(), block);
     }
 
+    /// Adds a block which can be looked up like any other, but is never written
+    pub fn push_block_read_only(&mut self, block: Block) {
+        self.read_only_blocks
+            .insert(block.description.identifier.clone());
+        self.blocks
+            .insert(block.description.identifier.clone(), block);
+    }
+    /// Adds an item which can be looked up like any other, but is never written
+    pub fn push_item_read_only(&mut self, item: Item) {
+        self.read_only_items
+            .insert(item.description.identifier.clone());
+        self.items.insert(item.description.identifier.clone(), item);
+    }
+
+    /// Replaces the file at `path` on write, so it shows up in [`Addon::dry_run`] too
+    pub fn push_extra_file(&mut self, path: PathBuf, contents: String) {
+        self.extra_files.insert(path, contents);
+    }
+
+    pub fn extra_file(&self, path: &Path) -> Option<&String> {
+        self.extra_files.get(path)
+    }
+
+    pub fn remove_block(&mut self, id: &str) -> Option<Block> {
+        self.read_only_blocks.remove(id);
+        self.blocks.remove(id)
+    }
+
+    pub fn remove_item(&mut self, id: &str) -> Option<Item> {
+        self.read_only_items.remove(id);
+        self.upgraded_items.remove(id);
+        self.items.remove(id)
+    }
+
+    pub fn is_block_read_only(&self, id: &str) -> bool {
+        self.read_only_blocks.contains(id)
+    }
+
+    pub fn is_item_read_only(&self, id: &str) -> bool {
+        self.read_only_items.contains(id)
+    }
+
     pub fn blocks_ref(&self) -> &HashMap<String, Block> {
         &self.blocks
     }
 
-    pub fn blocks_mut_ref(&mut self) -> &mut HashMap<String, Block> {
+    /// Includes read-only blocks, so it's kept to the crate
+    pub(crate) fn blocks_mut_ref(&mut self) -> &mut HashMap<String, Block> {
         &mut self.blocks
     }
 
+    /// Read-only blocks are never written, so they aren't handed out
+    pub fn block_mut(&mut self, id: &str) -> Option<&mut Block> {
+        if self.read_only_blocks.contains(id) {
+            log::warn!("Skipped an edit to the read-only block {id}");
+            return None;
+        }
+        self.blocks.get_mut(id)
+    }
+
     pub fn items_ref(&self) -> &HashMap<String, Item> {
         &self.items
     }
 
-    pub fn items_mut_ref(&mut self) -> &mut HashMap<String, Item> {
+    pub(crate) fn items_mut_ref(&mut self) -> &mut HashMap<String, Item> {
         &mut self.items
     }
 
-    pub fn write(&mut self) -> Result<(), AddonSerError> {
-        for (id, block) in &mut self.blocks {
-            let path = self.resolver.get_behaviour_block_output(id);
-            std::fs::write(path, serde_json::to_string(&block.to_json())?)?
+    /// Read-only items are never written, so they aren't handed out
+    pub fn item_mut(&mut self, id: &str) -> Option<&mut Item> {
+        if self.read_only_items.contains(id) {
+            log::warn!("Skipped an edit to the read-only item {id}");
+            return None;
         }
+        self.items.get_mut(id)
+    }
+
+    /// The pack folder a block was loaded from
+    pub fn block_pack(&self, id: &str) -> Option<PathBuf> {
+        self.resolver.get_block_pack(id)
+    }
 
+    /// The pack folder an item was loaded from
+    pub fn item_pack(&self, id: &str) -> Option<PathBuf> {
+        self.resolver.get_item_pack(id)
+    }
+
+    /// Upgrades every legacy item to the current single file format, see [`Item::upgrade`].
+    /// Read-only items are left as they are.
+    pub fn upgrade_legacy_items(&mut self) {
         for (id, item) in &mut self.items {
+            if item.is_legacy() && !self.read_only_items.contains(id) {
+                let had_resource = item
+                    .legacy
+                    .as_ref()
+                    .is_some_and(|e| e.resource_format_version.is_some());
+                item.upgrade();
+                if had_resource {
+                    self.upgraded_items.insert(id.clone());
+                }
+            }
+        }
+    }
+
+    /// Every file [`Addon::write`] touches, with the JSON it writes there or `None` for files it
+    /// removes
+    fn outputs(&mut self) -> Vec<(PathBuf, Option<Value>)> {
+        let mut outputs = vec![];
+
+        for (id, block) in &self.blocks {
+            if self.read_only_blocks.contains(id) {
+                continue;
+            }
+            let path = self.resolver.get_behaviour_block_output(id);
+            outputs.push((path, Some(block.to_json())));
+        }
+
+        for (id, item) in &self.items {
+            if self.read_only_items.contains(id) {
+                continue;
+            }
             let path = self.resolver.get_behaviour_item_output(id);
-            std::fs::write(path, serde_json::to_string(&item.to_json())?)?
+            outputs.push((path, Some(item.to_json())));
+
+            if let Some(resource) = item.resource_json() {
+                let path = self.resolver.get_resource_item_output(id);
+                outputs.push((path, Some(resource)));
+            } else if self.upgraded_items.contains(id) {
+                let path = self.resolver.get_resource_item_output(id);
+                if self.resolver.file_system().exists(&path) {
+                    outputs.push((path, None));
+                }
+            }
+        }
+
+        outputs
+    }
+
+    pub fn write(&mut self) -> Result<(), AddonSerError> {
+        for (path, json) in self.outputs() {
+            match json {
+                Some(json) => self
+                    .resolver
+                    .file_system()
+                    .write(&path, serde_json::to_string(&json)?.as_bytes())?,
+                None => self.resolver.file_system().remove(&path)?,
+            }
+        }
+        for (path, contents) in &self.extra_files {
+            self.resolver
+                .file_system()
+                .write(path, contents.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Compares what [`Addon::write`] would write with the files currently there, without
+    /// writing anything. Only files which change are returned, sorted by path.
+    pub fn dry_run(&mut self) -> Result<Vec<FileDiff>, AddonSerError> {
+        let mut diffs = vec![];
+        for (path, json) in self.outputs() {
+            let fs = self.resolver.file_system();
+            let old = if fs.exists(&path) {
+                Some(fs.read_to_string(&path)?)
+            } else {
+                None
+            };
+
+            // Files which aren't strict JSON, e.g. with comments, are compared line by line
+            match old
+                .as_deref()
+                .map(serde_json::from_str::<Value>)
+                .transpose()
+            {
+                Ok(old) => diffs.extend(FileDiff::new(path, old.as_ref(), json.as_ref())),
+                Err(_) => {
+                    let new = json.map(|e| serde_json::to_string_pretty(&e)).transpose()?;
+                    diffs.extend(FileDiff::text(path, old.as_deref(), new.as_deref()))
+                }
+            }
+        }
+        for (path, contents) in &self.extra_files {
+            let fs = self.resolver.file_system();
+            let old = if fs.exists(path) {
+                Some(fs.read_to_string(path)?)
+            } else {
+                None
+            };
+            diffs.extend(FileDiff::text(path.clone(), old.as_deref(), Some(contents)));
+        }
+
+        diffs.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(diffs)
+    }
+
+    /// Zips every behaviour and resource pack into a `.mcaddon` shaped archive, with the files
+    /// [`Addon::write`] would write in place of the originals. Nothing is written to the packs.
+    pub fn write_archive<W: Write + Seek>(&mut self, writer: W) -> Result<W, AddonSerError> {
+        let mut outputs = HashMap::new();
+        for (path, json) in self.outputs() {
+            let data = json.map(|e| serde_json::to_vec(&e)).transpose()?;
+            outputs.insert(path, data);
         }
+        for (path, contents) in &self.extra_files {
+            outputs.insert(path.clone(), Some(contents.clone().into_bytes()));
+        }
+
+        let mut packs = self.resolver.get_behaviour_bases();
+        packs.extend(self.resolver.get_resource_bases());
+
+        Ok(write_archive(
+            self.resolver.file_system(),
+            &packs,
+            &outputs,
+            writer,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::addon::addon::Addon;
+    use crate::addon::diff::{FileChange, JsonChange};
+    use crate::addon::path_resolver::default_impl::BaseResolver;
+    use crate::parser::addon_parser::{AddonParser, ParserConfig};
+    use crate::test_utils::block;
+    use crate::vfs::memory::MemoryFileSystem;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn dry_run_leaves_files() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        let item = json!({"format_version":"1.10","minecraft:item":{"description":{"identifier":"azur:berry"},"components":{"azur:edible":{}}}}).to_string();
+        fs.insert("BP/items/berry.json", item.clone());
+        fs.insert(
+            "RP/items/berry.json",
+            json!({"format_version":"1.10","minecraft:item":{"description":{"identifier":"azur:berry"},"components":{"minecraft:icon":{"texture":"azur_berry"}}}}).to_string(),
+        );
+
+        let mut addon = AddonParser::parse_addon_with(
+            fs.clone(),
+            "",
+            ParserConfig::builder()
+                .parse_block(true)
+                .parse_items(true)
+                .skip_bland(true)
+                .build(),
+        )?;
+        // Untouched legacy items are written back as they were read
+        assert!(addon.dry_run()?.is_empty());
+
+        addon.upgrade_legacy_items();
+        fs.insert("scripts/main.ts", "old");
+        addon.push_extra_file("scripts/main.ts".into(), "new".to_string());
+
+        let diffs = addon.dry_run()?;
+        assert_eq!(
+            diffs
+                .iter()
+                .map(|e| (e.path.clone(), e.change))
+                .collect::<Vec<_>>(),
+            vec![
+                (PathBuf::from("BP/items/berry.json"), FileChange::Modified),
+                (PathBuf::from("RP/items/berry.json"), FileChange::Removed),
+                (PathBuf::from("scripts/main.ts"), FileChange::Modified),
+            ]
+        );
+        assert!(diffs[0].changes.contains(&JsonChange::Changed {
+            path: "$['format_version']".into(),
+            old: json!("1.10"),
+            new: json!("1.21.40"),
+        }));
+
+        assert_eq!(fs.get("BP/items/berry.json").unwrap(), item.as_bytes());
+        assert!(fs.get("RP/items/berry.json").is_some());
+        assert_eq!(fs.get("scripts/main.ts").unwrap(), b"old");
+
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_compares_text() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        fs.insert("BP/blocks/azur_lamp.json", "// the old lamp\n{}");
+
+        let mut addon = Addon::new(BaseResolver::with_file_system("".into(), fs.clone()));
+        addon.push_block(block("azur:lamp", json!({})));
+
+        let diffs = addon.dry_run()?;
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, PathBuf::from("BP/blocks/azur_lamp.json"));
+        assert_eq!(diffs[0].change, FileChange::Modified);
+        // Without JSON on both sides only the lines are compared
+        assert!(diffs[0].changes.is_empty());
+        assert!(diffs[0].unified.contains("-// the old lamp"));
+        assert!(diffs[0].unified.contains("+  \"minecraft:block\": {"));
 
         Ok(())
     }
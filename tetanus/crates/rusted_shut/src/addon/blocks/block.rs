@@ -6,13 +6,15 @@ use crate::addon::menu_category::MenuCategory;
 use crate::addon::state::StateData;
 use crate::addon::traits::FormattedJsonSerialize;
 use crate::addon::translation::translation_service::TranslationManager;
-use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PlacementDirection {
     pub enabled_states: Vec<String>,
+    #[serde(default)]
     pub y_rotation_offset: i32,
 }
 
@@ -37,6 +39,27 @@ pub enum Trait {
     PlacementDirection(PlacementDirection),
 }
 
+/// Picks the variant by the trait's name, as both traits look the same without an offset
+fn deserialize_traits<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, Trait>, D::Error> {
+    HashMap::<String, Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, data)| {
+            let data = match name.as_str() {
+                "minecraft:placement_direction" => {
+                    serde_json::from_value(data).map(Trait::PlacementDirection)
+                }
+                "minecraft:placement_position" => {
+                    serde_json::from_value(data).map(Trait::PlacementPosition)
+                }
+                _ => serde_json::from_value(data),
+            };
+            data.map(|data| (name, data)).map_err(D::Error::custom)
+        })
+        .collect()
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BlockDescription {
     pub identifier: String,
@@ -45,9 +68,12 @@ pub struct BlockDescription {
     #[serde(default)]
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub states: HashMap<String, StateData>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_traits")]
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub traits: HashMap<String, Trait>,
+    /// Keys the game doesn't define, e.g. authoring shorthands handled by a description hook
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl TryInto<BlockDescription> for Value {
@@ -63,6 +89,8 @@ pub struct Block {
     pub components: ComponentStore,
     pub permutations: Vec<Permutation>,
     pub format_version: semver::Version,
+    /// Keys of `minecraft:block` besides `description`, `components` and `permutations`
+    pub extra: Map<String, Value>,
 }
 
 impl Block {
@@ -71,6 +99,11 @@ impl Block {
             return false;
         }
 
+        let custom_key = |key: &String| key.contains(':') && !key.starts_with("minecraft:");
+        if self.description.extra.keys().any(custom_key) || self.extra.keys().any(custom_key) {
+            return false;
+        }
+
         for p in &self.permutations {
             if p.components.contains_non_minecraft() {
                 return false;
@@ -93,13 +126,21 @@ impl FormattedJsonSerialize for Block {
     type Error = ComponentError;
 
     fn to_json(&self) -> Value {
+        let mut block = self.extra.clone();
+        block.insert("description".to_string(), json!(self.description));
+        block.insert("components".to_string(), self.components.to_json());
+        block.insert(
+            "permutations".to_string(),
+            self.permutations
+                .iter()
+                .map(|perm| perm.to_json())
+                .collect::<Vec<Value>>()
+                .into(),
+        );
+
         json!({
             "format_version": self.format_version.to_string(),
-            "minecraft:block": {
-                "description": self.description,
-                "components": self.components.to_json(),
-                "permutations": self.permutations.iter().map(|perm| perm.to_json()).collect::<Vec<Value>>()
-            }
+            "minecraft:block": block
         })
     }
 
@@ -145,11 +186,22 @@ impl FormattedJsonSerialize for Block {
             vec![]
         };
 
+        let extra = json
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| {
+                !["description", "components", "permutations"].contains(&key.as_str())
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
         Ok(Self {
             format_version: format,
             description,
             components,
             permutations,
+            extra,
         })
     }
 }
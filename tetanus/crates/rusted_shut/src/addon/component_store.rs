@@ -76,6 +76,15 @@ impl ComponentStore {
         self.components.insert(name, comp);
     }
 
+    pub fn ids(&self) -> impl Iterator<Item = &String> {
+        self.components.keys()
+    }
+
+    /// Moves every component of `other` into this store, replacing ones with the same id
+    pub fn extend(&mut self, other: ComponentStore) {
+        self.components.extend(other.components);
+    }
+
     pub fn contains_non_minecraft(&self) -> bool {
         for _ in self
             .components
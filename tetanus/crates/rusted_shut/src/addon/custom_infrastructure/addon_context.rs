@@ -0,0 +1,217 @@
+use crate::addon::addon::Addon;
+use crate::addon::blocks::block::Block;
+use crate::addon::blocks::permutation::Permutation;
+use crate::addon::component_store::ComponentStore;
+use crate::addon::items::item::Item;
+use std::path::PathBuf;
+
+/// A change to the addon, applied once the current pass is done
+pub enum AddonCommand {
+    PushBlock(Block, Option<PathBuf>),
+    PushItem(Item, Option<PathBuf>),
+    RemoveBlock(String),
+    RemoveItem(String),
+    EditBlock(String, Box<dyn FnOnce(&mut Block)>),
+    EditItem(String, Box<dyn FnOnce(&mut Item)>),
+}
+
+#[derive(Default)]
+pub struct AddonCommands {
+    commands: Vec<AddonCommand>,
+}
+
+impl AddonCommands {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push(&mut self, command: AddonCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn apply(&mut self, addon: &mut Addon) {
+        for command in self.commands.drain(..) {
+            match command {
+                AddonCommand::PushBlock(block, pack) => {
+                    let id = &block.description.identifier;
+                    if !addon.blocks_ref().contains_key(id) {
+                        addon.resolver.assign_block_output(id, pack.as_deref());
+                    }
+                    addon.push_block(block)
+                }
+                AddonCommand::PushItem(item, pack) => {
+                    let id = &item.description.identifier;
+                    if !addon.items_ref().contains_key(id) {
+                        addon.resolver.assign_item_output(id, pack.as_deref());
+                    }
+                    addon.push_item(item)
+                }
+                AddonCommand::RemoveBlock(id) => {
+                    addon.remove_block(&id);
+                }
+                AddonCommand::RemoveItem(id) => {
+                    addon.remove_item(&id);
+                }
+                AddonCommand::EditBlock(id, edit) => {
+                    if let Some(block) = addon.block_mut(&id) {
+                        edit(block)
+                    }
+                }
+                AddonCommand::EditItem(id, edit) => {
+                    if let Some(item) = addon.item_mut(&id) {
+                        edit(item)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn and(a: &str, b: &str) -> String {
+    format!("({a}) && ({b})")
+}
+
+/// The block permutation whose components are being applied
+#[derive(Debug, Clone)]
+pub struct PermutationContext {
+    pub index: usize,
+    pub condition: String,
+    splits: Vec<(String, ComponentStore)>,
+}
+
+impl PermutationContext {
+    pub fn new(index: usize, condition: String) -> Self {
+        Self {
+            index,
+            condition,
+            splits: Vec::new(),
+        }
+    }
+
+    pub fn narrow(&mut self, condition: &str) {
+        self.condition = and(&self.condition, condition);
+    }
+
+    /// Replaces the permutation with one copy per variant, each requiring the variant's condition
+    pub fn split(&mut self, variants: Vec<(String, ComponentStore)>) {
+        if self.splits.is_empty() {
+            self.splits = variants;
+            return;
+        }
+
+        self.splits = self
+            .splits
+            .iter()
+            .flat_map(|(condition, components)| {
+                variants.iter().map(move |(variant, extra)| {
+                    let mut components = components.clone();
+                    components.extend(extra.clone());
+                    (and(condition, variant), components)
+                })
+            })
+            .collect();
+    }
+
+    pub fn splits(&self) -> &[(String, ComponentStore)] {
+        &self.splits
+    }
+
+    pub fn into_permutations(self, components: ComponentStore) -> Vec<Permutation> {
+        if self.splits.is_empty() {
+            return vec![Permutation::new(self.condition, components)];
+        }
+
+        self.splits
+            .into_iter()
+            .map(|(variant, extra)| {
+                let mut components = components.clone();
+                components.extend(extra);
+                Permutation::new(and(&self.condition, &variant), components)
+            })
+            .collect()
+    }
+}
+
+/// What a custom component sees of the addon. Changes are queued and applied after the pass.
+pub struct AddonContext<'a> {
+    addon: &'a Addon,
+    commands: &'a mut AddonCommands,
+    owner_pack: Option<PathBuf>,
+    permutation: Option<PermutationContext>,
+}
+
+impl<'a> AddonContext<'a> {
+    pub fn new(
+        addon: &'a Addon,
+        commands: &'a mut AddonCommands,
+        owner_pack: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            addon,
+            commands,
+            owner_pack,
+            permutation: None,
+        }
+    }
+
+    pub fn addon(&self) -> &Addon {
+        self.addon
+    }
+
+    pub fn permutation(&self) -> Option<&PermutationContext> {
+        self.permutation.as_ref()
+    }
+
+    pub fn permutation_mut(&mut self) -> Option<&mut PermutationContext> {
+        self.permutation.as_mut()
+    }
+
+    pub(crate) fn set_permutation(&mut self, permutation: Option<PermutationContext>) {
+        self.permutation = permutation;
+    }
+
+    pub(crate) fn take_permutation(&mut self) -> Option<PermutationContext> {
+        self.permutation.take()
+    }
+
+    pub fn commands(&mut self) -> &mut AddonCommands {
+        self.commands
+    }
+
+    pub fn push_block(&mut self, block: Block) {
+        self.commands
+            .push(AddonCommand::PushBlock(block, self.owner_pack.clone()));
+    }
+
+    pub fn push_item(&mut self, item: Item) {
+        self.commands
+            .push(AddonCommand::PushItem(item, self.owner_pack.clone()));
+    }
+
+    pub fn remove_block(&mut self, id: &str) {
+        self.commands
+            .push(AddonCommand::RemoveBlock(id.to_string()));
+    }
+
+    pub fn remove_item(&mut self, id: &str) {
+        self.commands.push(AddonCommand::RemoveItem(id.to_string()));
+    }
+
+    pub fn edit_block<F: FnOnce(&mut Block) + 'static>(&mut self, id: &str, edit: F) {
+        self.commands
+            .push(AddonCommand::EditBlock(id.to_string(), Box::new(edit)));
+    }
+
+    pub fn edit_item<F: FnOnce(&mut Item) + 'static>(&mut self, id: &str, edit: F) {
+        self.commands
+            .push(AddonCommand::EditItem(id.to_string(), Box::new(edit)));
+    }
+}
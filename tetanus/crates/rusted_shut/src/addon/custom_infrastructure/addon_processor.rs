@@ -1,20 +1,61 @@
 use crate::addon::addon::Addon;
+use crate::addon::blocks::block::Block;
 use crate::addon::component::UnknownComponent;
+use crate::addon::component_store::ComponentStore;
+use crate::addon::custom_infrastructure::addon_context::{
+    AddonCommands, AddonContext, PermutationContext,
+};
 use crate::addon::custom_infrastructure::component::custom_block::{
     CustomBlockComponent, GenericBlockCustomComponent,
 };
 use crate::addon::custom_infrastructure::component::custom_item::{
     CustomItemComponent, GenericItemCustomComponent,
 };
-use std::cell::UnsafeCell;
-use std::collections::HashMap;
-use std::fmt::Debug;
+use crate::addon::custom_infrastructure::processing_report::ProcessingReport;
+use crate::addon::items::item::Item;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::fmt::{Debug, Display, Formatter};
+use std::time::Instant;
 use thiserror::Error;
 
 pub struct AddonProcessor<BlockError, ItemError, UserState> {
     block_components: HashMap<String, GenericBlockCustomComponent<BlockError, UserState>>,
     item_components: HashMap<String, GenericItemCustomComponent<ItemError, UserState>>,
+    block_hooks: HashMap<String, GenericBlockCustomComponent<BlockError, UserState>>,
+    item_hooks: HashMap<String, GenericItemCustomComponent<ItemError, UserState>>,
     user_state: UserState,
+    max_passes: Option<usize>,
+    report: ProcessingReport,
+}
+
+/// The order every kind of handler runs in, worked out once per [`AddonProcessor::process_addon`]
+struct HandlerOrder {
+    block_components: Vec<String>,
+    block_hooks: Vec<String>,
+    item_components: Vec<String>,
+    item_hooks: Vec<String>,
+}
+
+/// A block or item which still carried handled components after the last allowed pass
+#[derive(Debug, Clone)]
+pub struct PendingComponents {
+    /// `"block"` or `"item"`
+    pub kind: &'static str,
+    pub owner: String,
+    /// Components applied to the owner in the last pass, one of which keeps adding `remaining`
+    pub applied: Vec<String>,
+    pub remaining: Vec<String>,
+}
+
+impl Display for PendingComponents {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {:?} left {:?} behind",
+            self.kind, self.owner, self.applied, self.remaining
+        )
+    }
 }
 
 #[derive(Debug, Error)]
@@ -25,6 +66,95 @@ pub enum ProcessingError<BlockError: Debug, ItemError: Debug> {
     ItemError(ItemError),
     #[error("Custom component is an unexpected type")]
     ComponentInvalidType,
+    #[error("Custom components {0:?} have to run after each other")]
+    DependencyCycle(Vec<String>),
+    #[error("Processing didn't settle after {0} passes: {pending}", pending = .1.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    NoFixedPoint(usize, Vec<PendingComponents>),
+}
+
+/// The sorted identifiers not in `seen` yet, which are added to it
+fn unseen<'a>(ids: impl Iterator<Item = &'a String>, seen: &mut HashSet<String>) -> Vec<String> {
+    let mut ids = ids
+        .filter(|id| !seen.contains(*id))
+        .cloned()
+        .collect::<Vec<_>>();
+    ids.sort();
+    seen.extend(ids.iter().cloned());
+    ids
+}
+
+/// The component a handler is bound to. Components parsed into a typed component can't be handed
+/// to it and are skipped with a warning.
+fn bound_component(components: &ComponentStore, id: &str, owner: &str) -> Option<UnknownComponent> {
+    if let Some(component) = components.get_component_ref::<UnknownComponent>(id) {
+        return Some(component.clone());
+    }
+    if components.ids().any(|e| e == id) {
+        log::warn!("Skipped {id} on {owner}, it was parsed as a built in component");
+    }
+    None
+}
+
+/// Sorts handlers so each one runs after everything in its `runs_after`. Among handlers whose
+/// dependencies are done, higher priorities go first, then lower ids, so the order never depends
+/// on hashing. Dependencies on handlers which aren't bound are ignored.
+///
+/// ## Errors
+/// Every handler stuck in or behind a cycle
+fn handler_order<'a>(
+    handlers: impl Iterator<Item = (&'a str, i32, Vec<String>)>,
+) -> Result<Vec<String>, Vec<String>> {
+    let handlers = handlers.collect::<Vec<_>>();
+    let priorities = handlers
+        .iter()
+        .map(|(id, priority, _)| (*id, *priority))
+        .collect::<HashMap<_, _>>();
+
+    let mut waiting_on = HashMap::<&str, usize>::new();
+    let mut dependents = HashMap::<&str, Vec<&str>>::new();
+    for (id, _, runs_after) in &handlers {
+        let deps = runs_after
+            .iter()
+            .map(String::as_str)
+            .filter(|dep| priorities.contains_key(dep))
+            .collect::<BTreeSet<_>>();
+
+        waiting_on.insert(id, deps.len());
+        for dep in deps {
+            dependents.entry(dep).or_default().push(id);
+        }
+    }
+
+    let mut ready = waiting_on
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(id, _)| (priorities[id], Reverse(*id)))
+        .collect::<BinaryHeap<_>>();
+
+    let mut order = Vec::with_capacity(handlers.len());
+    while let Some((_, Reverse(id))) = ready.pop() {
+        order.push(id.to_string());
+
+        for dependent in dependents.get(id).into_iter().flatten() {
+            let count = waiting_on.get_mut(dependent).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push((priorities[dependent], Reverse(*dependent)));
+            }
+        }
+    }
+
+    if order.len() < handlers.len() {
+        let mut stuck = waiting_on
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(id, _)| id.to_string())
+            .collect::<Vec<_>>();
+        stuck.sort();
+        return Err(stuck);
+    }
+
+    Ok(order)
 }
 
 impl<BlockError: Debug, ItemError: Debug, UserState>
@@ -34,10 +164,33 @@ impl<BlockError: Debug, ItemError: Debug, UserState>
         Self {
             block_components: HashMap::new(),
             item_components: HashMap::new(),
+            block_hooks: HashMap::new(),
+            item_hooks: HashMap::new(),
             user_state: state,
+            max_passes: None,
+            report: ProcessingReport::default(),
         }
     }
 
+    /// Switches to fixed-point processing: passes are repeated until no block or item carries a
+    /// handled component anymore, e.g. because one component expanded into others.
+    ///
+    /// Processing fails with [`ProcessingError::NoFixedPoint`] if there's still work left after
+    /// `max_passes`.
+    pub fn fixed_point(&mut self, max_passes: usize) -> &mut Self {
+        self.max_passes = Some(max_passes);
+        self
+    }
+
+    /// What the last [`AddonProcessor::process_addon`] did
+    pub fn report(&self) -> &ProcessingReport {
+        &self.report
+    }
+
+    pub fn take_report(&mut self) -> ProcessingReport {
+        std::mem::take(&mut self.report)
+    }
+
     pub fn state_mut(&mut self) -> &mut UserState {
         &mut self.user_state
     }
@@ -154,128 +307,456 @@ impl<BlockError: Debug, ItemError: Debug, UserState>
         self
     }
 
+    /// Binds a block description hook to the `AddonProcessor`.
+    ///
+    /// Hooks are matched against keys outside of `components`: custom keys in `description` and
+    /// at the top level of `minecraft:block`. The key is removed before the hook is applied with
+    /// its value, and the block's components as `component_context`. Hooks run before components.
+    ///
+    /// # Arguments
+    /// * `comp` - The hook implementing `CustomBlockComponent`.
+    pub fn bind_block_description_hook<
+        T: CustomBlockComponent<Error = BlockError, UserState = UserState> + 'static,
+    >(
+        &mut self,
+        comp: T,
+    ) -> &mut Self {
+        self.block_hooks
+            .insert(T::static_id().to_string(), Box::new(comp));
+        self
+    }
+
+    pub fn bind_block_description_hook_box(
+        &mut self,
+        comp: Box<dyn CustomBlockComponent<Error = BlockError, UserState = UserState>>,
+    ) -> &mut Self {
+        self.block_hooks.insert(comp.id().to_string(), comp);
+        self
+    }
+
+    /// Binds an item description hook to the `AddonProcessor`, matched against custom keys in
+    /// `description` and at the top level of `minecraft:item`.
+    /// See [`AddonProcessor::bind_block_description_hook`].
+    ///
+    /// # Arguments
+    /// * `comp` - The hook implementing `CustomItemComponent`.
+    pub fn bind_item_description_hook<
+        T: CustomItemComponent<Error = ItemError, UserState = UserState> + 'static,
+    >(
+        &mut self,
+        comp: T,
+    ) -> &mut Self {
+        self.item_hooks
+            .insert(T::static_id().to_string(), Box::new(comp));
+        self
+    }
+
+    pub fn bind_item_description_hook_box(
+        &mut self,
+        comp: Box<dyn CustomItemComponent<Error = ItemError, UserState = UserState>>,
+    ) -> &mut Self {
+        self.item_hooks.insert(comp.id().to_string(), comp);
+        self
+    }
+
+    /// The order block components are applied in, rejecting dependency cycles
+    pub fn block_component_order(
+        &self,
+    ) -> Result<Vec<String>, ProcessingError<BlockError, ItemError>> {
+        handler_order(
+            self.block_components
+                .iter()
+                .map(|(id, comp)| (id.as_str(), comp.priority(), comp.runs_after())),
+        )
+        .map_err(ProcessingError::DependencyCycle)
+    }
+
+    /// The order item components are applied in, rejecting dependency cycles
+    pub fn item_component_order(
+        &self,
+    ) -> Result<Vec<String>, ProcessingError<BlockError, ItemError>> {
+        handler_order(
+            self.item_components
+                .iter()
+                .map(|(id, comp)| (id.as_str(), comp.priority(), comp.runs_after())),
+        )
+        .map_err(ProcessingError::DependencyCycle)
+    }
+
+    fn handler_order(&self) -> Result<HandlerOrder, ProcessingError<BlockError, ItemError>> {
+        Ok(HandlerOrder {
+            block_components: self.block_component_order()?,
+            block_hooks: handler_order(
+                self.block_hooks
+                    .iter()
+                    .map(|(id, comp)| (id.as_str(), comp.priority(), comp.runs_after())),
+            )
+            .map_err(ProcessingError::DependencyCycle)?,
+            item_components: self.item_component_order()?,
+            item_hooks: handler_order(
+                self.item_hooks
+                    .iter()
+                    .map(|(id, comp)| (id.as_str(), comp.priority(), comp.runs_after())),
+            )
+            .map_err(ProcessingError::DependencyCycle)?,
+        })
+    }
+
     /// ## Performs
-    /// Applies the registered components to the addon
+    /// Applies the registered components to the addon, blocks and items in identifier order and
+    /// components in handler order. Edits queued through the [`AddonContext`] are applied after
+    /// the block pass and after the item pass.
+    ///
+    /// Makes a single pass unless [`AddonProcessor::fixed_point`] was set.
     /// ## Returns
     /// The addon passed in; if error user must handle.
     pub fn process_addon(
         &mut self,
         mut addon: Addon,
     ) -> Result<Addon, ProcessingError<BlockError, ItemError>> {
-        addon = self
-            .process_blocks(addon)
-            .map_err(|err| ProcessingError::BlockError(err))?;
-
-        addon = self
-            .process_items(addon)
-            .map_err(|err| ProcessingError::ItemError(err))?;
-
-        Ok(addon)
-    }
-
-    fn process_blocks(&mut self, addon: Addon) -> Result<Addon, BlockError> {
-        unsafe {
-            let cell = UnsafeCell::new(addon);
-
-            let iteration_mut = cell.get().as_mut().unwrap_unchecked();
-            //let pass_addon = cell.get().as_mut().unwrap_unchecked();
-
-            for (_, blk) in iteration_mut.blocks_mut_ref().iter_mut() {
-                // Processes basic components
-                {
-                    let mut component_iter = blk.components.clone();
-
-                    for (component_id, information) in component_iter.non_minecraft_components_mut()
-                    {
-                        let mut component_ref = blk.components.clone();
-                        if let Some(func) = self.block_components.get_mut(component_id) {
-                            let base = information
-                                .as_any()
-                                .downcast_ref::<UnknownComponent>()
-                                .unwrap();
-
-                            func.apply_component(
-                                &base.data,
-                                blk,
-                                &mut component_ref,
-                                None,
-                                &mut self.user_state,
-                            )?;
-                            component_ref.remove_component(component_id);
-                            blk.components = component_ref;
-                        }
-                    }
-                }
+        let order = self.handler_order()?;
+        let mut commands = AddonCommands::new();
+        self.report = ProcessingReport::default();
 
-                // Processes permutations
-                {
-                    let mut permutation_iter = blk.permutations.clone();
-
-                    for (_idx, perm) in permutation_iter.iter_mut().enumerate() {
-                        let mut components = perm.components.clone();
-                        let mut component_ref = perm.components.clone();
-
-                        for (component_id, information) in components.non_minecraft_components_mut()
-                        {
-                            if let Some(func) = self.block_components.get_mut(component_id) {
-                                let base = information
-                                    .as_any()
-                                    .downcast_ref::<UnknownComponent>()
-                                    .unwrap();
-
-                                func.apply_component(
-                                    &base.data,
-                                    blk,
-                                    &mut component_ref,
-                                    None,
-                                    &mut self.user_state,
-                                )?;
-                                component_ref.remove_component(component_id);
-                            }
-                        }
-                        perm.components = component_ref;
-                    }
-                    blk.permutations = permutation_iter
-                }
+        let Some(max_passes) = self.max_passes else {
+            self.process_pass(&mut addon, &order, &mut commands)?;
+            self.report.collect_unhandled(&addon);
+            return Ok(addon);
+        };
+
+        let mut pending = Self::pending(&addon, &order);
+        for _ in 0..max_passes {
+            if pending.is_empty() {
+                self.report.collect_unhandled(&addon);
+                return Ok(addon);
             }
 
-            Ok(cell.into_inner())
+            self.process_pass(&mut addon, &order, &mut commands)?;
+
+            let applied = pending;
+            pending = Self::pending(&addon, &order);
+            if pending.is_empty() {
+                self.report.collect_unhandled(&addon);
+                return Ok(addon);
+            }
+
+            // Only kept for the error below
+            pending.iter_mut().for_each(|e| {
+                e.applied = applied
+                    .iter()
+                    .find(|a| a.kind == e.kind && a.owner == e.owner)
+                    .map(|a| a.remaining.clone())
+                    .unwrap_or_default()
+            });
         }
+
+        Err(ProcessingError::NoFixedPoint(max_passes, pending))
     }
 
-    fn process_items(&mut self, addon: Addon) -> Result<Addon, ItemError> {
-        unsafe {
-            let cell = UnsafeCell::new(addon);
+    /// Runs every block, then every item through the handlers. Content created on the way is
+    /// processed in the same pass, once the batch which created it is done.
+    fn process_pass(
+        &mut self,
+        addon: &mut Addon,
+        order: &HandlerOrder,
+        commands: &mut AddonCommands,
+    ) -> Result<(), ProcessingError<BlockError, ItemError>> {
+        let mut seen_blocks = HashSet::new();
+        let mut seen_items = HashSet::new();
 
-            let iteration_mut = cell.get().as_mut().unwrap_unchecked();
-            //let pass_addon = cell.get().as_mut().unwrap_unchecked();
+        loop {
+            let blocks = unseen(
+                addon
+                    .blocks_ref()
+                    .keys()
+                    .filter(|id| !addon.is_block_read_only(id)),
+                &mut seen_blocks,
+            );
+            if !blocks.is_empty() {
+                self.process_blocks(addon, &blocks, order, commands)
+                    .map_err(|err| ProcessingError::BlockError(err))?;
+                commands.apply(addon);
+                continue;
+            }
 
-            for (_, item) in iteration_mut.items_mut_ref().iter_mut() {
-                let mut components = item.components.clone();
+            let items = unseen(
+                addon
+                    .items_ref()
+                    .keys()
+                    .filter(|id| !addon.is_item_read_only(id)),
+                &mut seen_items,
+            );
+            if items.is_empty() {
+                return Ok(());
+            }
+            self.process_items(addon, &items, order, commands)
+                .map_err(|err| ProcessingError::ItemError(err))?;
+            commands.apply(addon);
+        }
+    }
 
-                for (component_id, information) in components.non_minecraft_components_mut() {
-                    let mut pass_ref = item.components.clone();
-                    if let Some(func) = self.item_components.get_mut(component_id) {
-                        let base = information
-                            .as_any()
-                            .downcast_ref::<UnknownComponent>()
-                            .unwrap();
+    /// Every block and item which still carries a handled component, in its components or
+    /// permutations, or a key handled by a description hook. Read-only ones are never processed.
+    fn pending(addon: &Addon, order: &HandlerOrder) -> Vec<PendingComponents> {
+        let mut pending = vec![];
 
-                        func.apply_component(
-                            &base.data,
-                            item,
-                            &mut pass_ref,
-                            None,
-                            &mut self.user_state,
-                        )?;
-                        pass_ref.remove_component(component_id);
+        let mut blocks = addon
+            .blocks_ref()
+            .iter()
+            .filter(|(id, _)| !addon.is_block_read_only(id))
+            .collect::<Vec<_>>();
+        blocks.sort_by_key(|(id, _)| *id);
+        for (id, blk) in blocks {
+            let hooks = order.block_hooks.iter().filter(|hook| {
+                blk.description.extra.contains_key(*hook) || blk.extra.contains_key(*hook)
+            });
+            let components = order.block_components.iter().filter(|comp| {
+                blk.components.ids().any(|e| e == *comp)
+                    || blk
+                        .permutations
+                        .iter()
+                        .any(|perm| perm.components.ids().any(|e| e == *comp))
+            });
+            let remaining = hooks.chain(components).cloned().collect::<Vec<_>>();
 
-                        item.components = pass_ref;
-                    }
+            if !remaining.is_empty() {
+                pending.push(PendingComponents {
+                    kind: "block",
+                    owner: id.clone(),
+                    applied: vec![],
+                    remaining,
+                });
+            }
+        }
+
+        let mut items = addon
+            .items_ref()
+            .iter()
+            .filter(|(id, _)| !addon.is_item_read_only(id))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(id, _)| *id);
+        for (id, item) in items {
+            let hooks = order.item_hooks.iter().filter(|hook| {
+                item.description.extra.contains_key(*hook) || item.extra.contains_key(*hook)
+            });
+            let components = order
+                .item_components
+                .iter()
+                .filter(|comp| item.components.ids().any(|e| e == *comp));
+            let remaining = hooks.chain(components).cloned().collect::<Vec<_>>();
+
+            if !remaining.is_empty() {
+                pending.push(PendingComponents {
+                    kind: "item",
+                    owner: id.clone(),
+                    applied: vec![],
+                    remaining,
+                });
+            }
+        }
+
+        pending
+    }
+
+    fn process_blocks(
+        &mut self,
+        addon: &mut Addon,
+        ids: &[String],
+        order: &HandlerOrder,
+        commands: &mut AddonCommands,
+    ) -> Result<(), BlockError> {
+        for id in ids {
+            // The block is taken out while its components run, so the rest of the addon can be
+            // handed out read-only next to it
+            let Some(mut blk) = addon.blocks_mut_ref().remove(id) else {
+                continue;
+            };
+            let pack = addon.block_pack(id);
+            let result = self.process_block(
+                &mut blk,
+                order,
+                &mut AddonContext::new(addon, commands, pack),
+            );
+            addon.blocks_mut_ref().insert(id.clone(), blk);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    fn process_block(
+        &mut self,
+        blk: &mut Block,
+        order: &HandlerOrder,
+        context: &mut AddonContext,
+    ) -> Result<(), BlockError> {
+        // Processes description hooks
+        for hook_id in &order.block_hooks {
+            let Some(data) = blk
+                .description
+                .extra
+                .remove(hook_id)
+                .or_else(|| blk.extra.remove(hook_id))
+            else {
+                continue;
+            };
+            let func = self.block_hooks.get_mut(hook_id).unwrap();
+
+            let start = Instant::now();
+            let mut component_ref = blk.components.clone();
+            func.apply_component(
+                &data,
+                blk,
+                &mut component_ref,
+                context,
+                &mut self.user_state,
+            )?;
+            blk.components = component_ref;
+            self.report
+                .record_block(hook_id, &blk.description.identifier, start.elapsed());
+        }
+
+        // Processes basic components
+        for component_id in &order.block_components {
+            let Some(base) =
+                bound_component(&blk.components, component_id, &blk.description.identifier)
+            else {
+                continue;
+            };
+            let func = self.block_components.get_mut(component_id).unwrap();
+
+            let start = Instant::now();
+            let mut component_ref = blk.components.clone();
+            func.apply_component(
+                &base.data,
+                blk,
+                &mut component_ref,
+                context,
+                &mut self.user_state,
+            )?;
+            component_ref.remove_component(component_id);
+            blk.components = component_ref;
+            self.report
+                .record_block(component_id, &blk.description.identifier, start.elapsed());
+        }
+
+        // Processes permutations
+        {
+            let permutation_iter = blk.permutations.clone();
+            let mut permutations = Vec::with_capacity(permutation_iter.len());
+
+            for (idx, perm) in permutation_iter.into_iter().enumerate() {
+                let mut component_ref = perm.components;
+                context.set_permutation(Some(PermutationContext::new(idx, perm.condition)));
+
+                for component_id in &order.block_components {
+                    let Some(base) =
+                        bound_component(&component_ref, component_id, &blk.description.identifier)
+                    else {
+                        continue;
+                    };
+                    let func = self.block_components.get_mut(component_id).unwrap();
+
+                    let start = Instant::now();
+                    func.apply_component(
+                        &base.data,
+                        blk,
+                        &mut component_ref,
+                        context,
+                        &mut self.user_state,
+                    )?;
+                    component_ref.remove_component(component_id);
+                    self.report.record_permutation(
+                        component_id,
+                        &blk.description.identifier,
+                        idx,
+                        start.elapsed(),
+                    );
                 }
+
+                let perm_context = context.take_permutation().unwrap();
+                permutations.extend(perm_context.into_permutations(component_ref));
             }
-            Ok(cell.into_inner())
+            blk.permutations = permutations
         }
+
+        Ok(())
+    }
+
+    fn process_items(
+        &mut self,
+        addon: &mut Addon,
+        ids: &[String],
+        order: &HandlerOrder,
+        commands: &mut AddonCommands,
+    ) -> Result<(), ItemError> {
+        for id in ids {
+            let Some(mut item) = addon.items_mut_ref().remove(id) else {
+                continue;
+            };
+            let pack = addon.item_pack(id);
+            let result = self.process_item(
+                &mut item,
+                order,
+                &mut AddonContext::new(addon, commands, pack),
+            );
+            addon.items_mut_ref().insert(id.clone(), item);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    fn process_item(
+        &mut self,
+        item: &mut Item,
+        order: &HandlerOrder,
+        context: &mut AddonContext,
+    ) -> Result<(), ItemError> {
+        for hook_id in &order.item_hooks {
+            let Some(data) = item
+                .description
+                .extra
+                .remove(hook_id)
+                .or_else(|| item.extra.remove(hook_id))
+            else {
+                continue;
+            };
+            let func = self.item_hooks.get_mut(hook_id).unwrap();
+
+            let start = Instant::now();
+            let mut pass_ref = item.components.clone();
+            func.apply_component(&data, item, &mut pass_ref, context, &mut self.user_state)?;
+            item.components = pass_ref;
+            self.report
+                .record_item(hook_id, &item.description.identifier, start.elapsed());
+        }
+
+        for component_id in &order.item_components {
+            let Some(base) =
+                bound_component(&item.components, component_id, &item.description.identifier)
+            else {
+                continue;
+            };
+            let func = self.item_components.get_mut(component_id).unwrap();
+
+            let start = Instant::now();
+            let mut pass_ref = item.components.clone();
+            func.apply_component(
+                &base.data,
+                item,
+                &mut pass_ref,
+                context,
+                &mut self.user_state,
+            )?;
+            pass_ref.remove_component(component_id);
+
+            item.components = pass_ref;
+            self.report
+                .record_item(component_id, &item.description.identifier, start.elapsed());
+        }
+
+        Ok(())
     }
 
     /// Hands back `UserState` over to callee
@@ -283,3 +764,552 @@ impl<BlockError: Debug, ItemError: Debug, UserState>
         self.user_state
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::addon::blocks::block::Block;
+    use crate::addon::component::UnknownComponent;
+    use crate::addon::component_store::ComponentStore;
+    use crate::addon::custom_infrastructure::addon_context::AddonContext;
+    use crate::addon::custom_infrastructure::addon_processor::{AddonProcessor, ProcessingError};
+    use crate::addon::custom_infrastructure::component::custom_base::CustomComponent;
+    use crate::addon::custom_infrastructure::component::custom_block::{
+        CustomBlockComponent, EmptyBlockState,
+    };
+    use crate::addon::custom_infrastructure::component::typed::{
+        ComponentConfigError, Typed, TypedBlockComponent,
+    };
+    use crate::addon::traits::FormattedJsonSerialize;
+    use crate::parser::addon_parser::{AddonParser, ParserConfig};
+    use crate::test_component;
+    use crate::test_utils::{addon, block, block_from};
+    use crate::vfs::memory::MemoryFileSystem;
+    use semver::Version;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::any::Any;
+    use std::path::PathBuf;
+
+    /// Copies the display name of the block named in its data, then removes that block
+    struct Absorb;
+
+    test_component!(Absorb, "azur:absorb");
+
+    impl CustomBlockComponent for Absorb {
+        type UserState = EmptyBlockState;
+        type Error = serde_json::Error;
+
+        fn block_clone(
+            &self,
+        ) -> Box<dyn CustomBlockComponent<Error = Self::Error, UserState = Self::UserState>>
+        {
+            Box::new(Absorb)
+        }
+
+        fn apply_component(
+            &mut self,
+            data: &Value,
+            owner: &mut Block,
+            _: &mut ComponentStore,
+            addon: &mut AddonContext,
+            _: &mut Self::UserState,
+        ) -> Result<(), Self::Error> {
+            let target = data.as_str().unwrap();
+
+            // The owner isn't visible through the context while it's being processed
+            assert!(addon
+                .addon()
+                .blocks_ref()
+                .get(&owner.description.identifier)
+                .is_none());
+
+            let tag = addon.addon().blocks_ref()[target].format_version.clone();
+            addon.edit_block(&owner.description.identifier.clone(), move |blk| {
+                blk.format_version = tag
+            });
+            addon.remove_block(target);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn deferred_edits() {
+        let mut water = block("azur:water", json!({}));
+        water.format_version = Version::new(1, 21, 50);
+        let addon = addon([
+            block("azur:sponge", json!({"azur:absorb":"azur:water"})),
+            water,
+        ]);
+
+        let mut processor =
+            AddonProcessor::<serde_json::Error, serde_json::Error, EmptyBlockState>::new(
+                EmptyBlockState,
+            );
+        processor.bind_block_component(Absorb);
+        let addon = processor.process_addon(addon).unwrap();
+
+        assert_eq!(addon.blocks_ref().len(), 1);
+        let sponge = &addon.blocks_ref()["azur:sponge"];
+        assert_eq!(sponge.format_version, Version::new(1, 21, 50));
+        assert!(sponge
+            .components
+            .get_component_ref::<UnknownComponent>("azur:absorb")
+            .is_none());
+    }
+
+    /// Records the order it's applied in, optionally adding another component
+    #[derive(Clone)]
+    struct Recorder {
+        id: &'static str,
+        priority: i32,
+        after: Vec<String>,
+        adds: Option<&'static str>,
+    }
+
+    impl CustomComponent for Recorder {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn id(&self) -> &str {
+            self.id
+        }
+
+        fn static_id() -> &'static str {
+            unreachable!()
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+
+        fn runs_after(&self) -> Vec<String> {
+            self.after.clone()
+        }
+    }
+
+    impl CustomBlockComponent for Recorder {
+        type UserState = Vec<String>;
+        type Error = serde_json::Error;
+
+        fn block_clone(
+            &self,
+        ) -> Box<dyn CustomBlockComponent<Error = Self::Error, UserState = Self::UserState>>
+        {
+            Box::new(self.clone())
+        }
+
+        fn apply_component(
+            &mut self,
+            _: &Value,
+            _: &mut Block,
+            component_context: &mut ComponentStore,
+            _: &mut AddonContext,
+            state: &mut Self::UserState,
+        ) -> Result<(), Self::Error> {
+            state.push(self.id.to_string());
+            if let Some(adds) = self.adds {
+                component_context.set_component_custom(
+                    UnknownComponent::new(json!({}), adds.to_string()),
+                    adds.to_string(),
+                );
+            }
+            Ok(())
+        }
+    }
+
+    fn recorder(id: &'static str, priority: i32, after: &[&str]) -> Box<Recorder> {
+        Box::new(Recorder {
+            id,
+            priority,
+            after: after.iter().map(|e| e.to_string()).collect(),
+            adds: None,
+        })
+    }
+
+    fn expander(id: &'static str, adds: &'static str) -> Box<Recorder> {
+        Box::new(Recorder {
+            adds: Some(adds),
+            ..*recorder(id, 0, &[])
+        })
+    }
+
+    #[test]
+    fn handler_ordering() {
+        let lamp = addon([block(
+            "azur:lamp",
+            json!({"azur:a":{},"azur:b":{},"azur:c":{},"azur:d":{}}),
+        )]);
+
+        let mut processor =
+            AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+        processor
+            .bind_block_component_box(recorder("azur:a", 0, &["azur:c"]))
+            .bind_block_component_box(recorder("azur:b", 0, &[]))
+            .bind_block_component_box(recorder("azur:c", 0, &["azur:missing"]))
+            .bind_block_component_box(recorder("azur:d", 5, &[]));
+
+        processor.process_addon(lamp).unwrap();
+        assert_eq!(
+            processor.disband(),
+            vec!["azur:d", "azur:b", "azur:c", "azur:a"]
+        );
+
+        let mut cyclic =
+            AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+        cyclic
+            .bind_block_component_box(recorder("azur:a", 0, &["azur:b"]))
+            .bind_block_component_box(recorder("azur:b", 0, &["azur:a"]))
+            .bind_block_component_box(recorder("azur:c", 0, &[]));
+
+        let result = cyclic.process_addon(addon([]));
+        assert!(matches!(
+            result,
+            Err(ProcessingError::DependencyCycle(ids)) if ids == vec!["azur:a", "azur:b"]
+        ));
+    }
+
+    #[test]
+    fn fixed_point_passes() {
+        let lamp = || addon([block("azur:lamp", json!({"azur:preset":{}}))]);
+        let processor = |max_passes| {
+            let mut processor =
+                AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+            processor
+                .bind_block_component_box(expander("azur:preset", "azur:glow"))
+                .bind_block_component_box(expander("azur:glow", "azur:flicker"))
+                .bind_block_component_box(recorder("azur:flicker", 0, &[]))
+                .fixed_point(max_passes);
+            processor
+        };
+
+        // `azur:glow` sorts before `azur:preset`, so each expansion takes its own pass
+        let mut settles = processor(3);
+        let addon = settles.process_addon(lamp()).unwrap();
+        assert_eq!(
+            settles.disband(),
+            vec!["azur:preset", "azur:glow", "azur:flicker"]
+        );
+        assert_eq!(addon.blocks_ref()["azur:lamp"].components.ids().count(), 0);
+
+        // Read-only blocks keep their components, without holding up the loop
+        let mut context = lamp();
+        context.push_block_read_only(block("azur:stone", json!({"azur:preset":{}})));
+        let context = processor(3).process_addon(context).unwrap();
+        assert_eq!(
+            context.blocks_ref()["azur:stone"].components.ids().count(),
+            1
+        );
+
+        let result = processor(2).process_addon(lamp());
+        assert!(matches!(
+            result,
+            Err(ProcessingError::NoFixedPoint(2, pending))
+                if pending[0].owner == "azur:lamp"
+                    && pending[0].applied == vec!["azur:glow"]
+                    && pending[0].remaining == vec!["azur:flicker"]
+        ));
+
+        let mut looping =
+            AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+        looping
+            .bind_block_component_box(expander("azur:preset", "azur:glow"))
+            .bind_block_component_box(expander("azur:glow", "azur:preset"))
+            .fixed_point(4);
+        let result = looping.process_addon(lamp());
+        assert!(matches!(
+            result,
+            Err(ProcessingError::NoFixedPoint(4, pending))
+                if pending[0].applied == vec!["azur:glow"]
+                    && pending[0].remaining == vec!["azur:glow"]
+        ));
+    }
+
+    /// Creates the block named in its data, carrying `azur:flicker`
+    struct Variants;
+
+    test_component!(Variants, "azur:variants");
+
+    impl CustomBlockComponent for Variants {
+        type UserState = Vec<String>;
+        type Error = serde_json::Error;
+
+        fn block_clone(
+            &self,
+        ) -> Box<dyn CustomBlockComponent<Error = Self::Error, UserState = Self::UserState>>
+        {
+            Box::new(Variants)
+        }
+
+        fn apply_component(
+            &mut self,
+            data: &Value,
+            _: &mut Block,
+            _: &mut ComponentStore,
+            addon: &mut AddonContext,
+            _: &mut Self::UserState,
+        ) -> Result<(), Self::Error> {
+            addon.push_block(block(data.as_str().unwrap(), json!({"azur:flicker":{}})));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn generated_content() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        fs.insert(
+            "library_bp/blocks/base.json",
+            json!({"format_version":"1.21.40","minecraft:block":{"description":{"identifier":"azur:base"},"components":{"azur:flicker":{}}}}).to_string(),
+        );
+        fs.insert(
+            "lamps_bp/blocks/lamp.json",
+            json!({"format_version":"1.21.40","minecraft:block":{"description":{"identifier":"azur:lamp"},"components":{"azur:variants":"azur:lamp_slab"}}}).to_string(),
+        );
+
+        let addon = AddonParser::parse_addon_with(
+            fs.clone(),
+            "",
+            ParserConfig::builder()
+                .parse_block(true)
+                .parse_items(true)
+                .skip_bland(true)
+                .bp_from_base("library_bp".to_string())
+                .extra_bp_from_base(vec!["lamps_bp".to_string()])
+                .build(),
+        )?;
+
+        let mut processor =
+            AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+        processor
+            .bind_block_component(Variants)
+            .bind_block_component_box(recorder("azur:flicker", 0, &[]));
+
+        let mut addon = processor.process_addon(addon)?;
+        // The slab went through the handlers within the same pass
+        assert_eq!(processor.disband(), vec!["azur:flicker", "azur:flicker"]);
+        assert_eq!(
+            addon.block_pack("azur:lamp_slab"),
+            Some(PathBuf::from("lamps_bp"))
+        );
+
+        addon.write()?;
+        let slab: Value =
+            serde_json::from_slice(&fs.get("lamps_bp/blocks/azur_lamp_slab.json").unwrap())?;
+        assert_eq!(slab["minecraft:block"]["components"], json!({}));
+
+        Ok(())
+    }
+
+    #[test]
+    fn description_hooks() {
+        let addon = addon([block_from(
+            json!({"description":{"identifier":"azur:lamp","azur:menu_preset":"decor","azur:note":"kept"},"azur:glow_all":{},"components":{}}),
+        )]);
+
+        let mut processor =
+            AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+        processor
+            .bind_block_description_hook_box(recorder("azur:menu_preset", 0, &[]))
+            .bind_block_description_hook_box(expander("azur:glow_all", "azur:flicker"))
+            .bind_block_component_box(recorder("azur:flicker", 0, &[]));
+
+        let addon = processor.process_addon(addon).unwrap();
+        // Hooks run before components, so the component a hook adds is applied in the same pass
+        assert_eq!(
+            processor.disband(),
+            vec!["azur:glow_all", "azur:menu_preset", "azur:flicker"]
+        );
+
+        let lamp = addon.blocks_ref()["azur:lamp"].to_json();
+        assert_eq!(
+            lamp["minecraft:block"]["description"],
+            json!({"identifier":"azur:lamp","menu_category":{"category":"none","is_hidden_in_commands":false},"azur:note":"kept"})
+        );
+        assert!(lamp["minecraft:block"].get("azur:glow_all").is_none());
+    }
+
+    #[test]
+    fn processing_report() {
+        let addon = addon([
+            block_from(
+                json!({"description":{"identifier":"azur:lamp","azur:menu_preset":"decor"},"components":{"azur:flicker":{},"azur:unknown":{}},"permutations":[{"condition":"true","components":{"azur:flicker":{}}}]}),
+            ),
+            block("azur:torch", json!({"azur:flicker":{}})),
+        ]);
+
+        let mut processor =
+            AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+        processor.bind_block_component_box(recorder("azur:flicker", 0, &[]));
+        processor.process_addon(addon).unwrap();
+
+        let report = processor.report();
+        let flicker = &report.handlers["azur:flicker"];
+        assert_eq!(
+            flicker.blocks.iter().collect::<Vec<_>>(),
+            vec!["azur:lamp", "azur:torch"]
+        );
+        assert_eq!(
+            flicker.permutations.iter().collect::<Vec<_>>(),
+            vec![&("azur:lamp".to_string(), 0)]
+        );
+        assert!(flicker.items.is_empty());
+
+        assert_eq!(
+            report.unhandled.keys().collect::<Vec<_>>(),
+            vec!["azur:menu_preset", "azur:unknown"]
+        );
+        assert!(report.unhandled["azur:unknown"].contains("azur:lamp"));
+    }
+
+    #[derive(Clone)]
+    struct Strength;
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct StrengthConfig {
+        value: f64,
+    }
+
+    test_component!(Strength, "azur:strength");
+
+    impl TypedBlockComponent for Strength {
+        type Config = StrengthConfig;
+        type UserState = EmptyBlockState;
+        type Error = ComponentConfigError;
+
+        fn apply(
+            &mut self,
+            config: Self::Config,
+            _: &mut Block,
+            component_context: &mut ComponentStore,
+            _: &mut AddonContext,
+            _: &mut Self::UserState,
+        ) -> Result<(), Self::Error> {
+            let id = "minecraft:destructible_by_mining".to_string();
+            component_context.set_component_box(
+                Box::new(UnknownComponent::new(json!(config.value), id.clone())),
+                id,
+            );
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn typed_config_errors() {
+        let mut processor =
+            AddonProcessor::<ComponentConfigError, ComponentConfigError, EmptyBlockState>::new(
+                EmptyBlockState,
+            );
+        processor.bind_block_component(Typed(Strength));
+
+        let processed = processor
+            .process_addon(addon([block(
+                "azur:ore",
+                json!({"azur:strength":{"value":2.5}}),
+            )]))
+            .unwrap();
+        assert_eq!(
+            processed.blocks_ref()["azur:ore"].to_json()["minecraft:block"]["components"],
+            json!({"minecraft:destructible_by_mining": 2.5})
+        );
+
+        let addon = addon([block_from(
+            json!({"description":{"identifier":"azur:ore"},"components":{"azur:strength":{"value":2.5}},"permutations":[{"condition":"true","components":{"azur:strength":{"value":1,"hardness":3}}}]}),
+        )]);
+        let Err(ProcessingError::BlockError(err)) = processor.process_addon(addon) else {
+            panic!("Unknown config field was accepted");
+        };
+        assert_eq!(
+            (err.owner.as_str(), err.permutation, err.component.as_str()),
+            ("azur:ore", Some(0), "azur:strength")
+        );
+        assert!(err
+            .to_string()
+            .starts_with("Invalid config for azur:strength on azur:ore in permutation 0: unknown field `hardness`"));
+    }
+
+    /// Splits its permutation per value of `azur:color` listed in its data and records the
+    /// permutations it sees
+    #[derive(Clone)]
+    struct ColorSplit;
+
+    test_component!(ColorSplit, "azur:color_split");
+
+    impl CustomBlockComponent for ColorSplit {
+        type UserState = Vec<String>;
+        type Error = serde_json::Error;
+
+        fn block_clone(
+            &self,
+        ) -> Box<dyn CustomBlockComponent<Error = Self::Error, UserState = Self::UserState>>
+        {
+            Box::new(self.clone())
+        }
+
+        fn apply_component(
+            &mut self,
+            data: &Value,
+            _: &mut Block,
+            _: &mut ComponentStore,
+            addon: &mut AddonContext,
+            state: &mut Self::UserState,
+        ) -> Result<(), Self::Error> {
+            let Some(perm) = addon.permutation_mut() else {
+                state.push("block".to_string());
+                return Ok(());
+            };
+            state.push(format!("{} {}", perm.index, perm.condition));
+
+            perm.narrow("q.block_state('azur:lit')");
+            let colors = serde_json::from_value::<Vec<String>>(data.clone())?;
+            perm.split(
+                colors
+                    .into_iter()
+                    .map(|color| {
+                        let mut components = ComponentStore::new();
+                        components.set_component_custom(
+                            UnknownComponent::new(json!(color), "minecraft:map_color".to_string()),
+                            "minecraft:map_color".to_string(),
+                        );
+                        (
+                            format!("q.block_state('azur:color') == '{color}'"),
+                            components,
+                        )
+                    })
+                    .collect(),
+            );
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn permutation_context() {
+        let addon = addon([block_from(
+            json!({"description":{"identifier":"azur:lamp"},"components":{"azur:color_split":[]},"permutations":[
+                {"condition":"a","components":{"minecraft:friction":0.5}},
+                {"condition":"b","components":{"azur:color_split":["red","blue"],"minecraft:friction":0.2}}
+            ]}),
+        )]);
+
+        let mut processor =
+            AddonProcessor::<serde_json::Error, serde_json::Error, Vec<String>>::new(vec![]);
+        processor.bind_block_component(ColorSplit);
+
+        let addon = processor.process_addon(addon).unwrap();
+        assert_eq!(processor.disband(), vec!["block", "1 b"]);
+
+        let lamp = addon.blocks_ref()["azur:lamp"].to_json();
+        assert_eq!(
+            lamp["minecraft:block"]["permutations"],
+            json!([
+                {"condition":"a","components":{"minecraft:friction":0.5}},
+                {"condition":"((b) && (q.block_state('azur:lit'))) && (q.block_state('azur:color') == 'red')","components":{"minecraft:friction":0.2,"minecraft:map_color":"red"}},
+                {"condition":"((b) && (q.block_state('azur:lit'))) && (q.block_state('azur:color') == 'blue')","components":{"minecraft:friction":0.2,"minecraft:map_color":"blue"}}
+            ])
+        );
+    }
+}
@@ -8,4 +8,14 @@ pub trait CustomComponent {
     fn static_id() -> &'static str
     where
         Self: Sized;
+
+    /// Among components which don't depend on each other, higher priorities run first
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// Ids of the components which have to run before this one
+    fn runs_after(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
@@ -1,6 +1,6 @@
-use crate::addon::addon::Addon;
 use crate::addon::blocks::block::Block;
 use crate::addon::component_store::ComponentStore;
+use crate::addon::custom_infrastructure::addon_context::AddonContext;
 use crate::addon::custom_infrastructure::component::custom_base::CustomComponent;
 use serde_json::Value;
 
@@ -14,12 +14,15 @@ pub trait CustomBlockComponent: CustomComponent {
         &self,
     ) -> Box<dyn CustomBlockComponent<Error = Self::Error, UserState = Self::UserState>>;
 
+    /// Applies the component with its `data`. Inside a permutation, `component_context` holds the
+    /// permutation's components and [`AddonContext::permutation_mut`] gives access to the
+    /// permutation itself.
     fn apply_component<'b>(
         &mut self,
         data: &Value,
         owner: &mut Block,
         component_context: &mut ComponentStore,
-        owning_addon: Option<&mut Addon>,
+        addon: &mut AddonContext,
         state: &'b mut Self::UserState,
     ) -> Result<(), Self::Error>;
 }
@@ -1,5 +1,5 @@
-use crate::addon::addon::Addon;
 use crate::addon::component_store::ComponentStore;
+use crate::addon::custom_infrastructure::addon_context::AddonContext;
 use crate::addon::custom_infrastructure::component::custom_base::CustomComponent;
 use crate::addon::items::item::Item;
 use serde_json::Value;
@@ -17,7 +17,7 @@ pub trait CustomItemComponent: CustomComponent {
         data: &Value,
         owner: &mut Item,
         component_context: &mut ComponentStore,
-        owning_addon: Option<&mut Addon>,
+        addon: &mut AddonContext,
         state: &mut Self::UserState,
     ) -> Result<(), Self::Error>;
 }
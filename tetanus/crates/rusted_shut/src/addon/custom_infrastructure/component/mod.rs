@@ -1,3 +1,4 @@
 pub mod custom_base;
 pub mod custom_block;
 pub mod custom_item;
+pub mod typed;
@@ -0,0 +1,151 @@
+use crate::addon::blocks::block::Block;
+use crate::addon::component_store::ComponentStore;
+use crate::addon::custom_infrastructure::addon_context::AddonContext;
+use crate::addon::custom_infrastructure::component::custom_base::CustomComponent;
+use crate::addon::custom_infrastructure::component::custom_block::CustomBlockComponent;
+use crate::addon::custom_infrastructure::component::custom_item::CustomItemComponent;
+use crate::addon::items::item::Item;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::any::Any;
+use thiserror::Error;
+
+/// A component's data didn't match its config type
+#[derive(Debug, Error)]
+#[error(
+    "Invalid config for {component} on {owner}{}: {source}",
+    .permutation.map(|e| format!(" in permutation {e}")).unwrap_or_default()
+)]
+pub struct ComponentConfigError {
+    pub owner: String,
+    pub permutation: Option<usize>,
+    pub component: String,
+    pub source: serde_json::Error,
+}
+
+/// A [`CustomBlockComponent`] whose data is deserialized into [`TypedBlockComponent::Config`]
+/// before it's applied. Bind it wrapped in [`Typed`].
+///
+/// Unknown fields are ignored unless the config type is marked `#[serde(deny_unknown_fields)]`.
+pub trait TypedBlockComponent: CustomComponent + Clone + 'static {
+    type Config: DeserializeOwned;
+    type UserState;
+    type Error: From<ComponentConfigError>;
+
+    fn apply(
+        &mut self,
+        config: Self::Config,
+        owner: &mut Block,
+        component_context: &mut ComponentStore,
+        addon: &mut AddonContext,
+        state: &mut Self::UserState,
+    ) -> Result<(), Self::Error>;
+}
+
+/// The item side of [`TypedBlockComponent`]
+pub trait TypedItemComponent: CustomComponent + Clone + 'static {
+    type Config: DeserializeOwned;
+    type UserState;
+    type Error: From<ComponentConfigError>;
+
+    fn apply(
+        &mut self,
+        config: Self::Config,
+        owner: &mut Item,
+        component_context: &mut ComponentStore,
+        addon: &mut AddonContext,
+        state: &mut Self::UserState,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Adapts a [`TypedBlockComponent`] or [`TypedItemComponent`] to the processor
+#[derive(Clone)]
+pub struct Typed<T>(pub T);
+
+impl<T> Typed<T> {
+    fn config<C: DeserializeOwned>(
+        id: &str,
+        owner: &str,
+        data: &Value,
+        addon: &AddonContext,
+    ) -> Result<C, ComponentConfigError> {
+        serde_json::from_value(data.clone()).map_err(|source| ComponentConfigError {
+            owner: owner.to_string(),
+            permutation: addon.permutation().map(|e| e.index),
+            component: id.to_string(),
+            source,
+        })
+    }
+}
+
+impl<T: CustomComponent + 'static> CustomComponent for Typed<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn static_id() -> &'static str {
+        T::static_id()
+    }
+
+    fn priority(&self) -> i32 {
+        self.0.priority()
+    }
+
+    fn runs_after(&self) -> Vec<String> {
+        self.0.runs_after()
+    }
+}
+
+impl<T: TypedBlockComponent> CustomBlockComponent for Typed<T> {
+    type UserState = T::UserState;
+    type Error = T::Error;
+
+    fn block_clone(
+        &self,
+    ) -> Box<dyn CustomBlockComponent<Error = Self::Error, UserState = Self::UserState>> {
+        Box::new(self.clone())
+    }
+
+    fn apply_component(
+        &mut self,
+        data: &Value,
+        owner: &mut Block,
+        component_context: &mut ComponentStore,
+        addon: &mut AddonContext,
+        state: &mut Self::UserState,
+    ) -> Result<(), Self::Error> {
+        let config = Self::config(self.0.id(), &owner.description.identifier, data, addon)?;
+        self.0.apply(config, owner, component_context, addon, state)
+    }
+}
+
+impl<T: TypedItemComponent> CustomItemComponent for Typed<T> {
+    type UserState = T::UserState;
+    type Error = T::Error;
+
+    fn item_clone(
+        &self,
+    ) -> Box<dyn CustomItemComponent<Error = Self::Error, UserState = Self::UserState>> {
+        Box::new(self.clone())
+    }
+
+    fn apply_component(
+        &mut self,
+        data: &Value,
+        owner: &mut Item,
+        component_context: &mut ComponentStore,
+        addon: &mut AddonContext,
+        state: &mut Self::UserState,
+    ) -> Result<(), Self::Error> {
+        let config = Self::config(self.0.id(), &owner.description.identifier, data, addon)?;
+        self.0.apply(config, owner, component_context, addon, state)
+    }
+}
@@ -1,2 +1,4 @@
+pub mod addon_context;
 pub mod addon_processor;
-pub mod component;
\ No newline at end of file
+pub mod component;
+pub mod processing_report;
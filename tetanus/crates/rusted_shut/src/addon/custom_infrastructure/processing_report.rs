@@ -0,0 +1,94 @@
+use crate::addon::addon::Addon;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+fn as_millis<S: Serializer>(time: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(time.as_secs_f64() * 1000.0)
+}
+
+/// What a single handler did during [`super::addon_processor::AddonProcessor::process_addon`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HandlerReport {
+    /// Blocks it was applied to, through their components or a description hook
+    pub blocks: BTreeSet<String>,
+    pub items: BTreeSet<String>,
+    /// Block permutations it was applied to, as block identifier and permutation index
+    pub permutations: BTreeSet<(String, usize)>,
+    /// Time spent in the handler, in milliseconds when serialized
+    #[serde(rename = "time_ms", serialize_with = "as_millis")]
+    pub time: Duration,
+}
+
+/// The record of one [`super::addon_processor::AddonProcessor::process_addon`] call
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessingReport {
+    pub handlers: BTreeMap<String, HandlerReport>,
+    /// Custom components and custom description keys left over after processing, with the
+    /// blocks and items still carrying them
+    pub unhandled: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ProcessingReport {
+    pub fn record_block(&mut self, handler: &str, block: &str, time: Duration) {
+        let report = self.handler(handler);
+        report.blocks.insert(block.to_string());
+        report.time += time;
+    }
+
+    pub fn record_permutation(&mut self, handler: &str, block: &str, idx: usize, time: Duration) {
+        let report = self.handler(handler);
+        report.permutations.insert((block.to_string(), idx));
+        report.time += time;
+    }
+
+    pub fn record_item(&mut self, handler: &str, item: &str, time: Duration) {
+        let report = self.handler(handler);
+        report.items.insert(item.to_string());
+        report.time += time;
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.handlers.values().map(|e| e.time).sum()
+    }
+
+    /// Collects every custom component and description key still left in `addon`
+    pub fn collect_unhandled(&mut self, addon: &Addon) {
+        let custom = |key: &&String| key.contains(':') && !key.starts_with("minecraft:");
+
+        for (id, blk) in addon.blocks_ref() {
+            let keys = blk
+                .components
+                .ids()
+                .chain(blk.permutations.iter().flat_map(|e| e.components.ids()))
+                .chain(blk.description.extra.keys())
+                .chain(blk.extra.keys())
+                .filter(custom);
+            for key in keys {
+                self.unhandled
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(id.clone());
+            }
+        }
+
+        for (id, item) in addon.items_ref() {
+            let keys = item
+                .components
+                .ids()
+                .chain(item.description.extra.keys())
+                .chain(item.extra.keys())
+                .filter(custom);
+            for key in keys {
+                self.unhandled
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(id.clone());
+            }
+        }
+    }
+
+    fn handler(&mut self, handler: &str) -> &mut HandlerReport {
+        self.handlers.entry(handler.to_string()).or_default()
+    }
+}
@@ -0,0 +1,348 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Lines of unchanged context around every hunk of a unified diff
+const CONTEXT_LINES: usize = 3;
+
+/// A single change between two JSON documents, at a JSONPath like `$['minecraft:block']['components']`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JsonChange {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// What [`crate::addon::addon::Addon::write`] would do to one file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiff {
+    pub path: PathBuf,
+    pub change: FileChange,
+    pub changes: Vec<JsonChange>,
+    /// The pretty printed old and new JSON as a unified diff
+    pub unified: String,
+}
+
+impl FileDiff {
+    /// Compares the JSON currently at `path` with the JSON that would be written there, `None`
+    /// meaning no file. Returns `None` if nothing changes.
+    pub fn new(path: PathBuf, old: Option<&Value>, new: Option<&Value>) -> Option<Self> {
+        let change = match (old, new) {
+            (None, None) => return None,
+            (None, Some(_)) => FileChange::Added,
+            (Some(_), None) => FileChange::Removed,
+            (Some(old), Some(new)) if old == new => return None,
+            (Some(_), Some(_)) => FileChange::Modified,
+        };
+
+        let mut changes = vec![];
+        match (old, new) {
+            (Some(old), Some(new)) => json_changes("$".to_string(), old, new, &mut changes),
+            (None, Some(new)) => changes.push(JsonChange::Added {
+                path: "$".to_string(),
+                value: new.clone(),
+            }),
+            (Some(old), None) => changes.push(JsonChange::Removed {
+                path: "$".to_string(),
+                value: old.clone(),
+            }),
+            (None, None) => unreachable!(),
+        }
+
+        let unified = unified_diff(
+            &path.to_string_lossy(),
+            &pretty_lines(old),
+            &pretty_lines(new),
+        );
+
+        Some(Self {
+            path,
+            change,
+            changes,
+            unified,
+        })
+    }
+
+    /// Compares files line by line, for files which aren't JSON. [`FileDiff::changes`] is empty.
+    pub fn text(path: PathBuf, old: Option<&str>, new: Option<&str>) -> Option<Self> {
+        let change = match (old, new) {
+            (None, None) => return None,
+            (None, Some(_)) => FileChange::Added,
+            (Some(_), None) => FileChange::Removed,
+            (Some(old), Some(new)) if old == new => return None,
+            (Some(_), Some(_)) => FileChange::Modified,
+        };
+
+        let lines = |text: Option<&str>| {
+            text.map(|e| e.lines().map(str::to_string).collect::<Vec<_>>())
+                .unwrap_or_default()
+        };
+        let unified = unified_diff(&path.to_string_lossy(), &lines(old), &lines(new));
+
+        Some(Self {
+            path,
+            change,
+            changes: vec![],
+            unified,
+        })
+    }
+}
+
+fn pretty_lines(json: Option<&Value>) -> Vec<String> {
+    json.map(|e| {
+        serde_json::to_string_pretty(e)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn json_changes(path: String, old: &Value, new: &Value, changes: &mut Vec<JsonChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let path = format!("{path}['{}']", key.replace('\'', "\\'"));
+                match new.get(key) {
+                    Some(new_value) => json_changes(path, old_value, new_value, changes),
+                    None => changes.push(JsonChange::Removed {
+                        path,
+                        value: old_value.clone(),
+                    }),
+                }
+            }
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                changes.push(JsonChange::Added {
+                    path: format!("{path}['{}']", key.replace('\'', "\\'")),
+                    value: new_value.clone(),
+                });
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for (idx, old_value) in old.iter().enumerate() {
+                let path = format!("{path}[{idx}]");
+                match new.get(idx) {
+                    Some(new_value) => json_changes(path, old_value, new_value, changes),
+                    None => changes.push(JsonChange::Removed {
+                        path,
+                        value: old_value.clone(),
+                    }),
+                }
+            }
+            for (idx, new_value) in new.iter().enumerate().skip(old.len()) {
+                changes.push(JsonChange::Added {
+                    path: format!("{path}[{idx}]"),
+                    value: new_value.clone(),
+                });
+            }
+        }
+        (old, new) if old != new => changes.push(JsonChange::Changed {
+            path,
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Line {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// Line based diff through the longest common subsequence of `old` and `new`
+fn diff_lines(old: &[String], new: &[String]) -> Vec<Line> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(i, j));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Line::Removed(i));
+            i += 1;
+        } else {
+            lines.push(Line::Added(j));
+            j += 1;
+        }
+    }
+    lines
+}
+
+fn unified_diff(name: &str, old: &[String], new: &[String]) -> String {
+    let lines = diff_lines(old, new);
+    let mut out = String::new();
+    let old_name = if old.is_empty() {
+        "/dev/null".to_string()
+    } else {
+        format!("a/{name}")
+    };
+    let new_name = if new.is_empty() {
+        "/dev/null".to_string()
+    } else {
+        format!("b/{name}")
+    };
+    writeln!(out, "--- {old_name}\n+++ {new_name}").unwrap();
+
+    let changed = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Line::Same(..)))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    let mut idx = 0;
+    while idx < changed.len() {
+        // Grow the hunk while the next change is close enough to share context
+        let start = changed[idx].saturating_sub(CONTEXT_LINES);
+        let mut end = changed[idx];
+        while idx + 1 < changed.len() && changed[idx + 1] - end <= CONTEXT_LINES * 2 {
+            idx += 1;
+            end = changed[idx];
+        }
+        let end = (end + CONTEXT_LINES + 1).min(lines.len());
+        idx += 1;
+
+        let hunk = &lines[start..end];
+        let (old_start, new_start) = lines[..start].iter().fold((0, 0), |(o, n), e| match e {
+            Line::Same(..) => (o + 1, n + 1),
+            Line::Removed(_) => (o + 1, n),
+            Line::Added(_) => (o, n + 1),
+        });
+        let old_len = hunk.iter().filter(|e| !matches!(e, Line::Added(_))).count();
+        let new_len = hunk
+            .iter()
+            .filter(|e| !matches!(e, Line::Removed(_)))
+            .count();
+
+        // Empty ranges point at the line before them, like diff -u does
+        let old_start = if old_len == 0 {
+            old_start
+        } else {
+            old_start + 1
+        };
+        let new_start = if new_len == 0 {
+            new_start
+        } else {
+            new_start + 1
+        };
+        writeln!(out, "@@ -{old_start},{old_len} +{new_start},{new_len} @@").unwrap();
+
+        for line in hunk {
+            match line {
+                Line::Same(i, _) => writeln!(out, " {}", old[*i]),
+                Line::Removed(i) => writeln!(out, "-{}", old[*i]),
+                Line::Added(j) => writeln!(out, "+{}", new[*j]),
+            }
+            .unwrap();
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::addon::diff::{FileChange, FileDiff, JsonChange};
+    use serde_json::json;
+
+    #[test]
+    fn json_and_unified_changes() {
+        let old = json!({"a": 1, "b": [1, 2], "c": {"d": true}});
+        let new = json!({"a": 2, "b": [1], "c": {"d": true, "e": "x"}});
+
+        let diff = FileDiff::new("BP/blocks/a.json".into(), Some(&old), Some(&new)).unwrap();
+        assert_eq!(diff.change, FileChange::Modified);
+        assert_eq!(
+            diff.changes,
+            vec![
+                JsonChange::Changed {
+                    path: "$['a']".into(),
+                    old: json!(1),
+                    new: json!(2)
+                },
+                JsonChange::Removed {
+                    path: "$['b'][1]".into(),
+                    value: json!(2)
+                },
+                JsonChange::Added {
+                    path: "$['c']['e']".into(),
+                    value: json!("x")
+                },
+            ]
+        );
+        assert_eq!(
+            diff.unified,
+            [
+                "--- a/BP/blocks/a.json",
+                "+++ b/BP/blocks/a.json",
+                "@@ -1,10 +1,10 @@",
+                " {",
+                "-  \"a\": 1,",
+                "+  \"a\": 2,",
+                "   \"b\": [",
+                "-    1,",
+                "-    2",
+                "+    1",
+                "   ],",
+                "   \"c\": {",
+                "-    \"d\": true",
+                "+    \"d\": true,",
+                "+    \"e\": \"x\"",
+                "   }",
+                " }",
+                "",
+            ]
+            .join("\n")
+        );
+
+        assert!(FileDiff::new("a.json".into(), Some(&old), Some(&old)).is_none());
+        let added = FileDiff::new("a.json".into(), None, Some(&new)).unwrap();
+        assert_eq!(added.change, FileChange::Added);
+        assert!(added
+            .unified
+            .starts_with("--- /dev/null\n+++ b/a.json\n@@ -0,0 +1,10 @@"));
+
+        let text = FileDiff::text("main.ts".into(), Some("a\nb"), Some("a\nc")).unwrap();
+        assert_eq!(text.change, FileChange::Modified);
+        assert_eq!(
+            text.unified,
+            "--- a/main.ts\n+++ b/main.ts\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"
+        );
+    }
+}
@@ -1,30 +1,79 @@
-use crate::addon::component::{ComponentError, FormattedComponentRegister};
+use crate::addon::component::{ComponentError, FormattedComponentRegister, UnknownComponent};
 use crate::addon::component_store::ComponentStore;
 use crate::addon::components::custom_components::MinecraftDisplayNameItem;
-use crate::addon::menu_category::MenuCategory;
+use crate::addon::menu_category::{Category, MenuCategory};
 use crate::addon::traits::FormattedJsonSerialize;
 use crate::addon::translation::translation_service::TranslationManager;
 use semver::Version;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
+
+/// Items below this format are split into a behaviour and a resource pack file
+pub const LEGACY_ITEM_FORMAT: Version = Version::new(1, 16, 100);
+/// The format [`Item::upgrade`] moves legacy items to
+pub const UPGRADED_ITEM_FORMAT: Version = Version::new(1, 21, 40);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ItemDescription {
     pub identifier: String,
     #[serde(default)]
     pub menu_category: MenuCategory,
+    /// The creative category of legacy items, replaced by `menu_category` in newer formats
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Keys the game doesn't define, e.g. authoring shorthands handled by a description hook
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// What a legacy item needs to be written back in its original two file shape
+#[derive(Debug, Default, Clone)]
+pub struct LegacyItem {
+    /// The `events` of the behaviour file, which newer formats don't support
+    pub events: Option<Value>,
+    /// Format of the resource pack half as written in its file, `None` if the item has no
+    /// resource file
+    pub resource_format_version: Option<String>,
+    /// Components merged in from the resource pack half, written back there
+    pub resource_components: Vec<String>,
 }
 
 #[derive(Debug)]
 pub struct Item {
     pub format_version: semver::Version,
+    /// `format_version` as written in the file, e.g. `"1.10"`, kept while it still matches
+    pub read_format_version: Option<String>,
     pub description: ItemDescription,
     pub components: ComponentStore,
+    /// Set for items older than [`LEGACY_ITEM_FORMAT`]
+    pub legacy: Option<LegacyItem>,
+    /// Keys of `minecraft:item` besides `description`, `components` and, for legacy items, `events`
+    pub extra: Map<String, Value>,
 }
 
 impl Item {
     pub fn is_bland(&self) -> bool {
+        let custom_key = |key: &String| key.contains(':') && !key.starts_with("minecraft:");
+
         !self.components.contains_non_minecraft()
+            && !self.description.extra.keys().any(custom_key)
+            && !self.extra.keys().any(custom_key)
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.legacy.is_some()
+    }
+
+    /// The block named by the item's `minecraft:block_placer`
+    pub fn placed_block(&self) -> Option<&str> {
+        let placer = &self
+            .components
+            .get_component_ref::<UnknownComponent>("minecraft:block_placer")?
+            .data;
+        match placer.get("block")? {
+            Value::String(block) => Some(block),
+            block => block.get("name")?.as_str(),
+        }
     }
 
     pub fn get_translation(&self) -> String {
@@ -35,18 +84,222 @@ impl Item {
             })
             .value
     }
+
+    /// Merges the resource pack half of a legacy item into this one.
+    ///
+    /// Its components, like `minecraft:icon`, are added to [`Item::components`] and remembered so
+    /// [`Item::resource_json`] can split them off again.
+    pub fn merge_resource_json(
+        &mut self,
+        json: &Value,
+        register: &FormattedComponentRegister,
+    ) -> Result<(), ComponentError> {
+        let format = parse_format_version(json)?;
+        let read_format = json["format_version"]
+            .as_str()
+            .map_or_else(|| format.to_string(), str::to_string);
+        let json = json
+            .get("minecraft:item")
+            .ok_or(ComponentError::MissingMember("minecraft:item", "an item"))?;
+
+        // Components are read first, so a failed merge leaves the item as it was
+        let components = json
+            .get("components")
+            .map(|e| ComponentStore::from_json(e, register, format.clone()))
+            .transpose()?;
+
+        if self.description.category.is_none() {
+            self.description.category = json
+                .pointer("/description/category")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+        }
+
+        let legacy = self.legacy.get_or_insert_with(Default::default);
+        legacy.resource_format_version = Some(read_format);
+
+        if let Some(components) = components {
+            legacy.resource_components.extend(components.ids().cloned());
+            self.components.extend(components);
+        }
+
+        Ok(())
+    }
+
+    /// The resource pack half of a legacy item, `None` for modern items and legacy items which
+    /// never had one
+    pub fn resource_json(&self) -> Option<Value> {
+        let legacy = self.legacy.as_ref()?;
+        let format = legacy.resource_format_version.as_ref()?;
+
+        let all = self.components.to_json();
+        let components = legacy
+            .resource_components
+            .iter()
+            .filter_map(|id| Some((id.clone(), all.get(id)?.clone())))
+            .collect::<serde_json::Map<_, _>>();
+
+        Some(json!({
+            "format_version": format,
+            "minecraft:item": {
+                "description": self.legacy_description(),
+                "components": components
+            }
+        }))
+    }
+
+    /// Turns a legacy item into a single file item of [`UPGRADED_ITEM_FORMAT`].
+    ///
+    /// Components whose shape changed are rewritten, `category` moves into `menu_category`.
+    /// Legacy `events` have no replacement and are dropped with a warning. Does nothing for modern
+    /// items.
+    pub fn upgrade(&mut self) {
+        let Some(legacy) = self.legacy.take() else {
+            return;
+        };
+
+        if let Some(events) = legacy.events.as_ref().and_then(Value::as_object) {
+            if !events.is_empty() {
+                let names = events.keys().cloned().collect::<Vec<_>>();
+                log::warn!(
+                    "Dropped the events of {}: {}",
+                    self.description.identifier,
+                    names.join(", ")
+                );
+            }
+        }
+
+        if let Some(category) = self.description.category.take() {
+            if let Ok(category) =
+                serde_json::from_value::<Category>(Value::String(category.to_lowercase()))
+            {
+                self.description.menu_category.category = category;
+            }
+        }
+
+        let components = self.components.to_json();
+        for (id, data) in components.as_object().into_iter().flatten() {
+            if let Some((new_id, new_data)) = upgrade_component(id, data) {
+                self.components.remove_component(id);
+                self.components.set_component_box(
+                    Box::new(UnknownComponent::new(new_data, new_id.to_string())),
+                    new_id.to_string(),
+                );
+            }
+        }
+
+        self.format_version = UPGRADED_ITEM_FORMAT;
+        self.read_format_version = None;
+    }
+
+    /// The `format_version` to write, the one read from the file while it still matches
+    fn format_json(&self) -> Value {
+        match &self.read_format_version {
+            Some(read) if parse_version(read).as_ref() == Some(&self.format_version) => {
+                json!(read)
+            }
+            _ => json!(self.format_version),
+        }
+    }
+
+    fn legacy_description(&self) -> Value {
+        let mut description = self.description.extra.clone();
+        description.insert("identifier".to_string(), json!(self.description.identifier));
+        if let Some(category) = &self.description.category {
+            description.insert("category".to_string(), json!(category));
+        }
+        description.into()
+    }
+}
+
+/// The new id and value of a legacy component, `None` if it can stay as it is
+fn upgrade_component(id: &str, data: &Value) -> Option<(&'static str, Value)> {
+    match (id, data) {
+        ("minecraft:icon", Value::Object(icon)) => {
+            Some(("minecraft:icon", icon.get("texture")?.clone()))
+        }
+        ("minecraft:foil", Value::Bool(_)) => Some(("minecraft:glint", data.clone())),
+        ("minecraft:max_damage", Value::Number(_)) => {
+            Some(("minecraft:durability", json!({ "max_durability": data })))
+        }
+        // Ticks in the legacy format, seconds in `use_modifiers`
+        ("minecraft:use_duration", Value::Number(ticks)) => Some((
+            "minecraft:use_modifiers",
+            json!({ "use_duration": ticks.as_f64()? / 20.0 }),
+        )),
+        ("minecraft:hand_equipped", Value::Bool(_)) => {
+            Some(("minecraft:hand_equipped", json!({ "value": data })))
+        }
+        ("minecraft:stacked_by_data", Value::Bool(_)) => {
+            Some(("minecraft:stacked_by_data", json!({ "value": data })))
+        }
+        ("minecraft:can_destroy_in_creative", Value::Bool(_)) => Some((
+            "minecraft:can_destroy_in_creative",
+            json!({ "value": data }),
+        )),
+        _ => None,
+    }
+}
+
+/// Reads the `format_version` of an item file. Legacy files often leave out the patch version,
+/// e.g. `"1.10"`.
+pub fn parse_format_version(json: &Value) -> Result<Version, ComponentError> {
+    let raw = json
+        .get("format_version")
+        .ok_or(ComponentError::MissingMember("format_version", "an item"))?;
+
+    let padded = match raw.as_str() {
+        Some(text) => Value::String(padded(text)),
+        None => raw.clone(),
+    };
+
+    Ok(serde_json::from_value(padded)?)
+}
+
+fn padded(text: &str) -> String {
+    match text.split('.').count() {
+        1 => format!("{text}.0.0"),
+        2 => format!("{text}.0"),
+        _ => text.to_string(),
+    }
+}
+
+fn parse_version(text: &str) -> Option<Version> {
+    Version::parse(&padded(text)).ok()
 }
 
 impl FormattedJsonSerialize for Item {
     type Error = ComponentError;
 
+    /// Legacy items only produce their behaviour pack half, see [`Item::resource_json`]
     fn to_json(&self) -> Value {
-        json!({
-            "format_version": self.format_version,
-            "minecraft:item": {
-                "description": self.description,
-                "components": self.components.to_json()
+        let mut item = self.extra.clone();
+
+        let Some(legacy) = &self.legacy else {
+            item.insert("description".to_string(), json!(self.description));
+            item.insert("components".to_string(), self.components.to_json());
+            return json!({
+                "format_version": self.format_json(),
+                "minecraft:item": item
+            });
+        };
+
+        let mut components = self.components.to_json();
+        if let Some(components) = components.as_object_mut() {
+            for id in &legacy.resource_components {
+                components.remove(id);
             }
+        }
+
+        item.insert("description".to_string(), self.legacy_description());
+        item.insert("components".to_string(), components);
+        if let Some(events) = &legacy.events {
+            item.insert("events".to_string(), events.clone());
+        }
+
+        json!({
+            "format_version": self.format_json(),
+            "minecraft:item": item
         })
     }
 
@@ -58,11 +311,8 @@ impl FormattedJsonSerialize for Item {
     where
         Self: Sized,
     {
-        let format: semver::Version = serde_json::from_value(
-            json.get("format_version")
-                .ok_or(ComponentError::MissingMember("format_version", "an item"))?
-                .clone(),
-        )?;
+        let format = parse_format_version(json)?;
+        let read_format = json["format_version"].as_str().map(str::to_string);
 
         let json = json
             .get("minecraft:item")
@@ -81,10 +331,30 @@ impl FormattedJsonSerialize for Item {
             format.clone(),
         )?;
 
+        let legacy = (format < LEGACY_ITEM_FORMAT).then(|| LegacyItem {
+            events: json.get("events").cloned(),
+            ..Default::default()
+        });
+
+        let extra = json
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| match key.as_str() {
+                "description" | "components" => false,
+                "events" => legacy.is_none(),
+                _ => true,
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
         Ok(Self {
+            read_format_version: read_format,
             format_version: format,
             description,
             components,
+            legacy,
+            extra,
         })
     }
 }
@@ -92,7 +362,7 @@ impl FormattedJsonSerialize for Item {
 #[cfg(test)]
 mod tests {
     use crate::addon::component::{ComponentError, FormattedComponentRegister, UnknownComponent};
-    use crate::addon::items::item::Item;
+    use crate::addon::items::item::{Item, UPGRADED_ITEM_FORMAT};
     use crate::addon::menu_category::Category;
     use crate::addon::traits::FormattedJsonSerialize;
     use semver::Version;
@@ -163,4 +433,64 @@ mod tests {
         assert_eq!(placeable_data["sound"]["id"], "bucket.empty_water");
         Ok(())
     }
+
+    #[test]
+    fn legacy_item_merge_and_upgrade() -> Result<(), ComponentError> {
+        let register = FormattedComponentRegister::init_items();
+        let behaviour = json!({"format_version":"1.10","minecraft:item":{"description":{"identifier":"azur:berry"},"components":{"minecraft:use_duration":32,"minecraft:foil":true,"azur:edible":{}},"events":{"azur:eaten":{"run_command":{"command":["say hi"]}}}}});
+        let resource = json!({"format_version":"1.10","minecraft:item":{"description":{"identifier":"azur:berry","category":"Nature"},"components":{"minecraft:icon":{"texture":"azur_berry"}}}});
+
+        let mut item = Item::from_json(&behaviour, &register, Version::new(0, 0, 0))?;
+        assert!(item.is_legacy());
+        assert_eq!(item.format_version, Version::new(1, 10, 0));
+
+        item.merge_resource_json(&resource, &register)?;
+        assert!(item
+            .components
+            .get_component_ref::<UnknownComponent>("minecraft:icon")
+            .is_some());
+
+        // Both halves come back out as they went in
+        let written = item.to_json();
+        assert_eq!(
+            written["minecraft:item"]["components"],
+            json!({"minecraft:use_duration":32,"minecraft:foil":true,"azur:edible":{}})
+        );
+        assert_eq!(
+            written["minecraft:item"]["events"],
+            behaviour["minecraft:item"]["events"]
+        );
+        // Short legacy formats aren't padded unless the item is upgraded
+        assert_eq!(written["format_version"], "1.10");
+        let written_resource = item.resource_json().unwrap();
+        assert_eq!(written_resource["format_version"], "1.10");
+        assert_eq!(
+            written_resource["minecraft:item"],
+            resource["minecraft:item"]
+        );
+
+        item.upgrade();
+        assert!(!item.is_legacy());
+        assert!(item.resource_json().is_none());
+        assert_eq!(item.format_version, UPGRADED_ITEM_FORMAT);
+        assert_eq!(item.description.menu_category.category, Category::Nature);
+
+        let upgraded = item.to_json();
+        assert_eq!(upgraded["format_version"], "1.21.40");
+        assert_eq!(
+            upgraded["minecraft:item"]["components"],
+            json!({
+                "minecraft:icon": "azur_berry",
+                "minecraft:glint": true,
+                "minecraft:use_modifiers": {"use_duration": 1.6},
+                "azur:edible": {}
+            })
+        );
+        assert!(upgraded["minecraft:item"].get("events").is_none());
+        assert!(upgraded["minecraft:item"]["description"]
+            .get("category")
+            .is_none());
+
+        Ok(())
+    }
 }
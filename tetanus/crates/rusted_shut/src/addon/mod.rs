@@ -3,6 +3,7 @@ pub mod blocks;
 pub mod component;
 pub mod component_store;
 pub mod components;
+pub mod diff;
 pub mod custom_infrastructure;
 pub mod items;
 pub mod mainifest;
@@ -1,5 +1,6 @@
+use crate::vfs::file_system::FileSystem;
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub trait AddonPathResolver: Debug {
     fn get_behaviour_block_base(&mut self) -> PathBuf {
@@ -18,30 +19,76 @@ pub trait AddonPathResolver: Debug {
 
         let str = format!("{}.json", id.to_owned().replace(":", "_"));
 
-        path.with_file_name(str)
+        path.join(str)
     }
     fn get_behaviour_item_output(&mut self, id: &str) -> PathBuf {
         let path = self.get_behaviour_item_base();
 
         let str = format!("{}.json", id.to_owned().replace(":", "_"));
 
-        path.with_file_name(str)
+        path.join(str)
+    }
+
+    /// Called once for every block created while processing the addon, before it's added.
+    /// `pack` is the behaviour pack of the block or item which created it, if known.
+    fn assign_block_output(&mut self, _id: &str, _pack: Option<&Path>) {}
+    /// Called once for every item created while processing the addon, before it's added.
+    /// `pack` is the behaviour pack of the block or item which created it, if known.
+    fn assign_item_output(&mut self, _id: &str, _pack: Option<&Path>) {}
+
+    fn get_resource_item_base(&mut self) -> PathBuf {
+        let mut base = self.get_resource_base();
+        base.push("items");
+        base
+    }
+
+    /// Where the resource pack half of a legacy item is written
+    fn get_resource_item_output(&mut self, id: &str) -> PathBuf {
+        self.get_resource_item_base()
+            .join(format!("{}.json", id.replace(":", "_")))
     }
 
     fn get_behaviour_base(&mut self) -> PathBuf;
     fn get_resource_base(&mut self) -> PathBuf;
+
+    /// Every behaviour pack of the addon, starting with `get_behaviour_base`
+    fn get_behaviour_bases(&mut self) -> Vec<PathBuf> {
+        vec![self.get_behaviour_base()]
+    }
+    /// Every resource pack of the addon, starting with `get_resource_base`
+    fn get_resource_bases(&mut self) -> Vec<PathBuf> {
+        vec![self.get_resource_base()]
+    }
+
+    /// The behaviour pack a block was loaded from, `None` if the resolver doesn't know the block
+    fn get_block_pack(&self, _id: &str) -> Option<PathBuf> {
+        None
+    }
+    /// The behaviour pack an item was loaded from, `None` if the resolver doesn't know the item
+    fn get_item_pack(&self, _id: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Where the resolved paths are read from and written to
+    fn file_system(&self) -> &dyn FileSystem;
 }
 
 pub mod default_impl {
     use crate::addon::path_resolver::AddonPathResolver;
+    use crate::vfs::disk::DiskFileSystem;
+    use crate::vfs::file_system::FileSystem;
     use std::path::PathBuf;
 
     #[derive(Debug)]
-    pub struct BaseResolver(PathBuf);
+    pub struct BaseResolver(PathBuf, Box<dyn FileSystem>);
 
     impl BaseResolver {
         pub fn new(output_base: PathBuf) -> Self {
-            Self(output_base)
+            Self::with_file_system(output_base, DiskFileSystem)
+        }
+
+        pub fn with_file_system<F: FileSystem + 'static>(output_base: PathBuf, fs: F) -> Self {
+            Self(output_base, Box::new(fs))
         }
     }
 
@@ -57,5 +104,9 @@ pub mod default_impl {
             r.push("RP");
             r
         }
+
+        fn file_system(&self) -> &dyn FileSystem {
+            self.1.as_ref()
+        }
     }
 }
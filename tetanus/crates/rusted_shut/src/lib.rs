@@ -1,3 +1,7 @@
 #![allow(dead_code)]
 pub mod addon;
 pub mod parser;
+pub mod pipeline;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod vfs;
@@ -1,74 +1,204 @@
 use crate::addon::addon::Addon;
 use crate::addon::blocks::block::Block;
 use crate::addon::component::{ComponentError, FormattedComponentRegister};
-use crate::addon::items::item::Item;
+use crate::addon::items::item::{parse_format_version, Item, LEGACY_ITEM_FORMAT};
 use crate::addon::path_resolver::AddonPathResolver;
 use crate::addon::traits::FormattedJsonSerialize;
 use crate::parser::addon_parser::AddonParseError::{FSError, JsonError};
+use crate::parser::path_filter::PathFilter;
+use crate::vfs::archive::{detect_packs, open_archive, ArchiveError};
+use crate::vfs::disk::DiskFileSystem;
+use crate::vfs::file_system::FileSystem;
+use crate::vfs::memory::MemoryFileSystem;
 use bon::Builder;
 use semver::Version;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
-use walkdir::WalkDir;
 
+#[derive(Debug, Clone)]
+struct SourceFile {
+    pack: usize,
+    relative: Box<Path>,
+}
+
+/// Resolves paths for one or more behaviour and resource packs, the first ones being primary
 #[derive(Debug)]
 pub struct ParsedAddonResolver {
-    block_path_lookup: HashMap<String, Box<Path>>,
-    item_path_lookup: HashMap<String, Box<Path>>,
+    block_path_lookup: HashMap<String, SourceFile>,
+    item_path_lookup: HashMap<String, SourceFile>,
+    resource_item_lookup: HashMap<String, SourceFile>,
     base: PathBuf,
-    rp_from_base: PathBuf,
-    bp_from_base: PathBuf,
+    rp_from_base: Vec<PathBuf>,
+    bp_from_base: Vec<PathBuf>,
+    file_system: Box<dyn FileSystem>,
 }
 
 impl AddonPathResolver for ParsedAddonResolver {
     fn get_behaviour_block_output(&mut self, id: &str) -> PathBuf {
-        let mut x = self.get_behaviour_block_base().clone();
         if let Some(r) = self.block_path_lookup.get(id) {
-            x.push(r.to_path_buf());
+            let mut x = self.behaviour_pack(r.pack);
+            x.push("blocks");
+            x.push(&r.relative);
             x
         } else {
             self.get_behaviour_block_base()
-                .with_file_name(format!("{}.json", id.replace(":", "_")))
+                .join(format!("{}.json", id.replace(":", "_")))
         }
     }
 
     fn get_behaviour_item_output(&mut self, id: &str) -> PathBuf {
-        let mut x = self.get_behaviour_item_base().clone();
         if let Some(r) = self.item_path_lookup.get(id) {
-            x.push(r.to_path_buf());
+            let mut x = self.behaviour_pack(r.pack);
+            x.push("items");
+            x.push(&r.relative);
             x
         } else {
-            self.get_behaviour_block_base()
-                .with_file_name(format!("{}.json", id.replace(":", "_")))
+            self.get_behaviour_item_base()
+                .join(format!("{}.json", id.replace(":", "_")))
+        }
+    }
+
+    fn assign_block_output(&mut self, id: &str, pack: Option<&Path>) {
+        let source = self.generated_source(id, pack);
+        self.block_path_lookup
+            .entry(id.to_string())
+            .or_insert(source);
+    }
+
+    fn assign_item_output(&mut self, id: &str, pack: Option<&Path>) {
+        let source = self.generated_source(id, pack);
+        self.item_path_lookup
+            .entry(id.to_string())
+            .or_insert(source);
+    }
+
+    fn get_resource_item_output(&mut self, id: &str) -> PathBuf {
+        if let Some(r) = self.resource_item_lookup.get(id) {
+            let mut x = self.base.join(&self.rp_from_base[r.pack]);
+            x.push("items");
+            x.push(&r.relative);
+            x
+        } else {
+            self.get_resource_item_base()
+                .join(format!("{}.json", id.replace(":", "_")))
         }
     }
 
     fn get_behaviour_base(&mut self) -> PathBuf {
-        let mut r = self.base.clone();
-        r.push(&self.bp_from_base);
-        r
+        self.behaviour_pack(0)
     }
 
     fn get_resource_base(&mut self) -> PathBuf {
         let mut r = self.base.clone();
-        r.push(&self.rp_from_base);
+        r.push(&self.rp_from_base[0]);
         r
     }
+
+    fn get_behaviour_bases(&mut self) -> Vec<PathBuf> {
+        (0..self.bp_from_base.len())
+            .map(|idx| self.behaviour_pack(idx))
+            .collect()
+    }
+
+    fn get_resource_bases(&mut self) -> Vec<PathBuf> {
+        self.rp_from_base
+            .iter()
+            .map(|rp| self.base.join(rp))
+            .collect()
+    }
+
+    fn get_block_pack(&self, id: &str) -> Option<PathBuf> {
+        Some(self.behaviour_pack(self.block_path_lookup.get(id)?.pack))
+    }
+
+    fn get_item_pack(&self, id: &str) -> Option<PathBuf> {
+        Some(self.behaviour_pack(self.item_path_lookup.get(id)?.pack))
+    }
+
+    fn file_system(&self) -> &dyn FileSystem {
+        self.file_system.as_ref()
+    }
 }
 
 impl ParsedAddonResolver {
     pub fn new(base: PathBuf, bp_from_base: PathBuf, rp_from_base: PathBuf) -> Self {
+        Self::with_file_system(base, bp_from_base, rp_from_base, Box::new(DiskFileSystem))
+    }
+
+    pub fn with_file_system(
+        base: PathBuf,
+        bp_from_base: PathBuf,
+        rp_from_base: PathBuf,
+        file_system: Box<dyn FileSystem>,
+    ) -> Self {
+        Self::from_packs(base, vec![bp_from_base], vec![rp_from_base], file_system)
+    }
+
+    pub fn with_packs(
+        base: PathBuf,
+        bp_from_base: Vec<PathBuf>,
+        rp_from_base: Vec<PathBuf>,
+        file_system: Box<dyn FileSystem>,
+    ) -> Result<Self, AddonParseError> {
+        if bp_from_base.is_empty() || rp_from_base.is_empty() {
+            return Err(AddonParseError::MissingPacks);
+        }
+
+        Ok(Self::from_packs(
+            base,
+            bp_from_base,
+            rp_from_base,
+            file_system,
+        ))
+    }
+
+    fn from_packs(
+        base: PathBuf,
+        bp_from_base: Vec<PathBuf>,
+        rp_from_base: Vec<PathBuf>,
+        file_system: Box<dyn FileSystem>,
+    ) -> Self {
         Self {
             base,
             block_path_lookup: HashMap::new(),
             item_path_lookup: HashMap::new(),
+            resource_item_lookup: HashMap::new(),
             bp_from_base,
             rp_from_base,
+            file_system,
         }
     }
+
+    fn behaviour_pack(&self, idx: usize) -> PathBuf {
+        let mut r = self.base.clone();
+        r.push(&self.bp_from_base[idx]);
+        r
+    }
+
+    fn generated_source(&self, id: &str, pack: Option<&Path>) -> SourceFile {
+        let pack = pack
+            .and_then(|pack| {
+                (0..self.bp_from_base.len()).find(|idx| self.behaviour_pack(*idx) == pack)
+            })
+            .unwrap_or(0);
+
+        SourceFile {
+            pack,
+            relative: PathBuf::from(format!("{}.json", id.replace(":", "_"))).into_boxed_path(),
+        }
+    }
+
+    fn json_files(&self, base: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+        Ok(self
+            .file_system
+            .walk_files(base)?
+            .into_iter()
+            .filter(|path| path.extension() == Some(OsStr::new("json")))
+            .collect())
+    }
 }
 
 #[derive(Builder)]
@@ -76,10 +206,26 @@ pub struct ParserConfig {
     parse_block: bool,
     parse_items: bool,
     skip_bland: bool,
+    /// With `skip_bland`, keeps bland content loaded as read-only context instead of dropping it
+    #[builder(default)]
+    bland_as_read_only: bool,
     rp_from_base: Option<String>,
     bp_from_base: Option<String>,
+    #[builder(default)]
+    extra_bp_from_base: Vec<String>,
+    #[builder(default)]
+    extra_rp_from_base: Vec<String>,
     block_register: Option<FormattedComponentRegister>,
     item_register: Option<FormattedComponentRegister>,
+    /// Globs relative to the behaviour pack, e.g. `blocks/azur_*.json`. Empty includes everything.
+    #[builder(default)]
+    block_include: Vec<String>,
+    #[builder(default)]
+    block_exclude: Vec<String>,
+    #[builder(default)]
+    item_include: Vec<String>,
+    #[builder(default)]
+    item_exclude: Vec<String>,
 }
 
 #[derive(Error, Debug)]
@@ -90,6 +236,14 @@ pub enum AddonParseError {
     FSError(#[from] std::io::Error),
     #[error(transparent)]
     ComponentError(#[from] ComponentError),
+    #[error(transparent)]
+    GlobError(#[from] globset::Error),
+    #[error(transparent)]
+    ArchiveError(#[from] ArchiveError),
+    #[error("{0} is defined in both {1} and {2}")]
+    DuplicateIdentifier(String, PathBuf, PathBuf),
+    #[error("A workspace needs at least one behaviour and one resource pack")]
+    MissingPacks,
 }
 
 pub struct AddonParser;
@@ -99,33 +253,106 @@ impl AddonParser {
         folder_base: P,
         config: ParserConfig,
     ) -> Result<Addon, AddonParseError> {
-        let mut resolver = ParsedAddonResolver::new(
+        Self::parse_addon_with(DiskFileSystem, folder_base, config)
+    }
+
+    pub fn parse_addon_with<F: FileSystem + 'static, P: AsRef<Path>>(
+        file_system: F,
+        folder_base: P,
+        config: ParserConfig,
+    ) -> Result<Addon, AddonParseError> {
+        let bp = config.bp_from_base.clone().unwrap_or("BP".to_string());
+        let rp = config.rp_from_base.clone().unwrap_or("RP".to_string());
+
+        let resolver = ParsedAddonResolver::with_packs(
             folder_base.as_ref().to_path_buf(),
-            config
-                .bp_from_base
-                .as_ref()
-                .unwrap_or(&"BP".to_string())
-                .into(),
-            config
-                .rp_from_base
-                .as_ref()
-                .unwrap_or(&"RP".to_string())
-                .into(),
-        );
+            std::iter::once(&bp)
+                .chain(&config.extra_bp_from_base)
+                .map(PathBuf::from)
+                .collect(),
+            std::iter::once(&rp)
+                .chain(&config.extra_rp_from_base)
+                .map(PathBuf::from)
+                .collect(),
+            Box::new(file_system),
+        )?;
 
-        let blocks = Self::parse_blocks(&mut resolver, &config)?;
-        let items = Self::parse_items(&mut resolver, &config)?;
+        Self::parse_with_resolver(resolver, &config)
+    }
+
+    /// Loads an addon from a `.mcaddon` or `.mcpack`, use [`Addon::write_archive`] to export it
+    pub fn parse_archive<P: AsRef<Path>>(
+        archive: P,
+        config: ParserConfig,
+    ) -> Result<Addon, AddonParseError> {
+        Self::parse_archive_file_system(open_archive(archive)?, config)
+    }
+
+    pub fn parse_archive_file_system(
+        file_system: MemoryFileSystem,
+        config: ParserConfig,
+    ) -> Result<Addon, AddonParseError> {
+        let mut layout = detect_packs(&file_system)?;
+        if layout.behaviour.is_empty() {
+            layout.behaviour.push(
+                config
+                    .bp_from_base
+                    .clone()
+                    .unwrap_or("BP".to_string())
+                    .into(),
+            );
+        }
+        if layout.resource.is_empty() {
+            layout.resource.push(
+                config
+                    .rp_from_base
+                    .clone()
+                    .unwrap_or("RP".to_string())
+                    .into(),
+            );
+        }
+
+        let resolver = ParsedAddonResolver::with_packs(
+            PathBuf::new(),
+            layout.behaviour,
+            layout.resource,
+            Box::new(file_system),
+        )?;
+
+        Self::parse_with_resolver(resolver, &config)
+    }
+
+    fn parse_with_resolver(
+        mut resolver: ParsedAddonResolver,
+        config: &ParserConfig,
+    ) -> Result<Addon, AddonParseError> {
+        let blocks = Self::parse_blocks(&mut resolver, config)?;
+        let kept_blocks = blocks
+            .iter()
+            .flatten()
+            .filter(|(_, read_only)| !read_only)
+            .map(|(b, _)| b.description.identifier.clone())
+            .collect::<HashSet<_>>();
+        let items = Self::parse_items(&mut resolver, config, &kept_blocks)?;
 
         let mut addon = Addon::new(resolver);
 
         if let Some(v) = blocks {
-            for b in v {
-                addon.push_block(b)
+            for (b, read_only) in v {
+                if read_only {
+                    addon.push_block_read_only(b)
+                } else {
+                    addon.push_block(b)
+                }
             }
         }
         if let Some(v) = items {
-            for i in v {
-                addon.push_item(i)
+            for (i, read_only) in v {
+                if read_only {
+                    addon.push_item_read_only(i)
+                } else {
+                    addon.push_item(i)
+                }
             }
         }
 
@@ -135,61 +362,68 @@ impl AddonParser {
     fn parse_blocks(
         resolver: &mut ParsedAddonResolver,
         parser_config: &ParserConfig,
-    ) -> Result<Option<Vec<Block>>, AddonParseError> {
+    ) -> Result<Option<Vec<(Block, bool)>>, AddonParseError> {
         let mut blocks = vec![];
 
         if !parser_config.parse_block {
             return Ok(None);
         }
 
-        let base_path = resolver.get_behaviour_block_base();
+        let filter = PathFilter::new(&parser_config.block_include, &parser_config.block_exclude)?;
 
-        for file in WalkDir::new(resolver.get_behaviour_block_base())
-            .into_iter()
-            .filter_map(|e| {
-                let e = e.ok();
-                if let Some(e) = e {
-                    if e.file_type().is_file() {
-                        if e.path().extension() != Some(OsStr::new("json")) {
-                            None
-                        } else {
-                            Some(e)
-                        }
-                    } else {
-                        None
-                    }
-                } else {
-                    None
+        for (pack, pack_base) in resolver.get_behaviour_bases().into_iter().enumerate() {
+            let base_path = pack_base.join("blocks");
+
+            for file in resolver.json_files(&base_path)? {
+                if !filter.is_match(file.strip_prefix(&pack_base).unwrap()) {
+                    continue;
                 }
-            })
-        {
-            let data = std::fs::read_to_string(file.path()).map_err(|e| FSError(e))?;
-            let raw_json = &serde_json::from_str::<Value>(&data).map_err(|e| JsonError(e))?;
-
-            let blk = Block::from_json(
-                raw_json,
-                parser_config
-                    .block_register
-                    .as_ref()
-                    .unwrap_or(&FormattedComponentRegister::init_blocks()),
-                Version::new(0, 0, 0),
-            )
-            .map_err(|e| AddonParseError::ComponentError(e))?;
-
-            if parser_config.skip_bland && blk.is_bland() {
-                continue;
-            }
 
-            resolver.block_path_lookup.insert(
-                blk.description.identifier.clone(),
-                file.path()
-                    .strip_prefix(&base_path)
-                    .unwrap()
-                    .to_path_buf()
-                    .into_boxed_path(),
-            );
+                let data = resolver
+                    .file_system()
+                    .read_to_string(&file)
+                    .map_err(|e| FSError(e))?;
+                let raw_json = &serde_json::from_str::<Value>(&data).map_err(|e| JsonError(e))?;
+
+                let blk = Block::from_json(
+                    raw_json,
+                    parser_config
+                        .block_register
+                        .as_ref()
+                        .unwrap_or(&FormattedComponentRegister::init_blocks()),
+                    Version::new(0, 0, 0),
+                )
+                .map_err(|e| AddonParseError::ComponentError(e))?;
+
+                let read_only = parser_config.skip_bland && blk.is_bland();
+                if read_only && !parser_config.bland_as_read_only {
+                    continue;
+                }
+
+                let id = &blk.description.identifier;
+                let existing = resolver.block_path_lookup.get(id).map(|e| e.pack);
+                if let Some(existing) = existing.filter(|e| *e != pack) {
+                    return Err(AddonParseError::DuplicateIdentifier(
+                        id.clone(),
+                        resolver.behaviour_pack(existing),
+                        pack_base,
+                    ));
+                }
+
+                resolver.block_path_lookup.insert(
+                    id.clone(),
+                    SourceFile {
+                        pack,
+                        relative: file
+                            .strip_prefix(&base_path)
+                            .unwrap()
+                            .to_path_buf()
+                            .into_boxed_path(),
+                    },
+                );
 
-            blocks.push(blk);
+                blocks.push((blk, read_only));
+            }
         }
 
         Ok(Some(blocks))
@@ -198,60 +432,282 @@ impl AddonParser {
     fn parse_items(
         resolver: &mut ParsedAddonResolver,
         parser_config: &ParserConfig,
-    ) -> Result<Option<Vec<Item>>, AddonParseError> {
+        kept_blocks: &HashSet<String>,
+    ) -> Result<Option<Vec<(Item, bool)>>, AddonParseError> {
         if !parser_config.parse_items {
             return Ok(None);
         }
         let mut items = vec![];
-        let base_path = resolver.get_behaviour_item_base();
+        let filter = PathFilter::new(&parser_config.item_include, &parser_config.item_exclude)?;
+        let fallback_register = FormattedComponentRegister::init_items();
+        let register = parser_config
+            .item_register
+            .as_ref()
+            .unwrap_or(&fallback_register);
 
-        for file in WalkDir::new(resolver.get_behaviour_item_base())
-            .into_iter()
-            .filter_map(|e| {
-                let e = e.ok();
-                if let Some(e) = e {
-                    if e.file_type().is_file() {
-                        if e.path().extension() != Some(OsStr::new("json")) {
-                            None
-                        } else {
-                            Some(e)
+        let mut resource_halves = Self::legacy_resource_items(resolver, &filter)?;
+
+        for (pack, pack_base) in resolver.get_behaviour_bases().into_iter().enumerate() {
+            let base_path = pack_base.join("items");
+
+            for file in resolver.json_files(&base_path)? {
+                if !filter.is_match(file.strip_prefix(&pack_base).unwrap()) {
+                    continue;
+                }
+
+                let data = resolver.file_system().read_to_string(&file)?;
+                let raw_json = &serde_json::from_str::<Value>(&data)?;
+
+                let mut item = Item::from_json(raw_json, register, Version::new(0, 0, 0))?;
+                if item.is_legacy() {
+                    if let Some((json, source)) =
+                        resource_halves.remove(&item.description.identifier)
+                    {
+                        match item.merge_resource_json(&json, register) {
+                            Ok(()) => {
+                                resolver
+                                    .resource_item_lookup
+                                    .insert(item.description.identifier.clone(), source);
+                            }
+                            Err(e) => log::warn!(
+                                "Skipped the resource file of {}: {e}",
+                                item.description.identifier
+                            ),
                         }
-                    } else {
-                        None
                     }
-                } else {
-                    None
                 }
-            })
-        {
-            let data = std::fs::read_to_string(file.path())?;
-            let raw_json = &serde_json::from_str::<Value>(&data)?;
-
-            let item = Item::from_json(
-                raw_json,
-                parser_config
-                    .item_register
-                    .as_ref()
-                    .unwrap_or(&FormattedComponentRegister::init_items()),
-                Version::new(0, 0, 0),
-            )?;
-
-            if parser_config.skip_bland && item.is_bland() {
-                continue;
-            }
 
-            resolver.item_path_lookup.insert(
-                item.description.identifier.clone(),
-                file.path()
-                    .strip_prefix(&base_path)
-                    .unwrap()
-                    .to_path_buf()
-                    .into_boxed_path(),
-            );
+                // Items placing a kept block are kept along with it, filters may edit them too
+                let places_kept = item.placed_block().is_some_and(|e| kept_blocks.contains(e));
+                let read_only = parser_config.skip_bland && item.is_bland() && !places_kept;
+                if read_only && !parser_config.bland_as_read_only {
+                    continue;
+                }
+
+                let id = &item.description.identifier;
+                let existing = resolver.item_path_lookup.get(id).map(|e| e.pack);
+                if let Some(existing) = existing.filter(|e| *e != pack) {
+                    return Err(AddonParseError::DuplicateIdentifier(
+                        id.clone(),
+                        resolver.behaviour_pack(existing),
+                        pack_base,
+                    ));
+                }
+
+                resolver.item_path_lookup.insert(
+                    id.clone(),
+                    SourceFile {
+                        pack,
+                        relative: file
+                            .strip_prefix(&base_path)
+                            .unwrap()
+                            .to_path_buf()
+                            .into_boxed_path(),
+                    },
+                );
 
-            items.push(item);
+                items.push((item, read_only));
+            }
         }
 
         Ok(Some(items))
     }
+
+    fn legacy_resource_items(
+        resolver: &mut ParsedAddonResolver,
+        filter: &PathFilter,
+    ) -> Result<HashMap<String, (Value, SourceFile)>, AddonParseError> {
+        let mut halves = HashMap::new();
+
+        for (pack, pack_base) in resolver.get_resource_bases().into_iter().enumerate() {
+            let base_path = pack_base.join("items");
+
+            for file in resolver.json_files(&base_path)? {
+                if !filter.is_match(file.strip_prefix(&pack_base).unwrap()) {
+                    continue;
+                }
+
+                let json = match serde_json::from_str::<Value>(
+                    &resolver.file_system().read_to_string(&file)?,
+                ) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        log::warn!("Skipped {}: {e}", file.display());
+                        continue;
+                    }
+                };
+
+                let legacy = parse_format_version(&json).is_ok_and(|e| e < LEGACY_ITEM_FORMAT);
+                let id = json
+                    .pointer("/minecraft:item/description/identifier")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+
+                if let (true, Some(id)) = (legacy, id) {
+                    let source = SourceFile {
+                        pack,
+                        relative: file
+                            .strip_prefix(&base_path)
+                            .unwrap()
+                            .to_path_buf()
+                            .into_boxed_path(),
+                    };
+                    halves.insert(id, (json, source));
+                }
+            }
+        }
+
+        Ok(halves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::addon_parser::{AddonParseError, AddonParser, ParserConfig};
+    use crate::vfs::memory::MemoryFileSystem;
+    use serde_json::{json, Value};
+    use std::path::PathBuf;
+
+    fn block(id: &str) -> String {
+        json!({"format_version":"1.21.40","minecraft:block":{"description":{"identifier":id},"components":{"azur:glow":{}}}}).to_string()
+    }
+
+    fn config() -> ParserConfig {
+        ParserConfig::builder()
+            .parse_block(true)
+            .parse_items(true)
+            .skip_bland(true)
+            .bp_from_base("library_bp".to_string())
+            .extra_bp_from_base(vec!["lamps_bp".to_string()])
+            .build()
+    }
+
+    #[test]
+    fn multi_pack_workspace() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        fs.insert("library_bp/blocks/base.json", block("azur:base"));
+        fs.insert("lamps_bp/blocks/deco/lamp.json", block("azur:lamp"));
+
+        let mut addon = AddonParser::parse_addon_with(fs.clone(), "", config())?;
+
+        assert_eq!(addon.blocks_ref().len(), 2);
+        assert_eq!(
+            addon.block_pack("azur:base"),
+            Some(PathBuf::from("library_bp"))
+        );
+        assert_eq!(
+            addon.block_pack("azur:lamp"),
+            Some(PathBuf::from("lamps_bp"))
+        );
+
+        fs.remove("library_bp/blocks/base.json");
+        fs.remove("lamps_bp/blocks/deco/lamp.json");
+        addon.write()?;
+
+        assert!(fs.get("library_bp/blocks/base.json").is_some());
+        assert!(fs.get("lamps_bp/blocks/deco/lamp.json").is_some());
+        assert_eq!(fs.paths().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bland_read_only_context() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        fs.insert("BP/blocks/lamp.json", block("azur:lamp"));
+        fs.insert(
+            "BP/blocks/stone.json",
+            json!({"format_version":"1.21.40","minecraft:block":{"description":{"identifier":"azur:stone"},"components":{}}}).to_string(),
+        );
+
+        let mut addon = AddonParser::parse_addon_with(
+            fs.clone(),
+            "",
+            ParserConfig::builder()
+                .parse_block(true)
+                .parse_items(true)
+                .skip_bland(true)
+                .bland_as_read_only(true)
+                .build(),
+        )?;
+
+        assert!(addon.blocks_ref().contains_key("azur:stone"));
+        assert!(addon.is_block_read_only("azur:stone"));
+        assert!(!addon.is_block_read_only("azur:lamp"));
+        assert!(addon.block_mut("azur:stone").is_none());
+        assert!(addon.block_mut("azur:lamp").is_some());
+
+        fs.remove("BP/blocks/lamp.json");
+        fs.remove("BP/blocks/stone.json");
+        addon.write()?;
+
+        assert_eq!(fs.paths(), vec![PathBuf::from("BP/blocks/lamp.json")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_identifier_across_packs() {
+        let fs = MemoryFileSystem::new();
+        fs.insert("library_bp/blocks/lamp.json", block("azur:lamp"));
+        fs.insert("lamps_bp/blocks/lamp.json", block("azur:lamp"));
+
+        let result = AddonParser::parse_addon_with(fs, "", config());
+        assert!(matches!(
+            result,
+            Err(AddonParseError::DuplicateIdentifier(id, _, _)) if id == "azur:lamp"
+        ));
+
+        // Within a single pack, the last one read replaces the others
+        let fs = MemoryFileSystem::new();
+        fs.insert("lamps_bp/blocks/lamp.json", block("azur:lamp"));
+        fs.insert("lamps_bp/blocks/old/lamp.json", block("azur:lamp"));
+        let addon = AddonParser::parse_addon_with(fs, "", config()).unwrap();
+        assert!(addon.blocks_ref().contains_key("azur:lamp"));
+    }
+
+    #[test]
+    fn legacy_items_across_packs() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        fs.insert(
+            "BP/items/berry.json",
+            json!({"format_version":"1.10","minecraft:item":{"description":{"identifier":"azur:berry"},"components":{"azur:edible":{}}}}).to_string(),
+        );
+        fs.insert(
+            "RP/items/food/berry.json",
+            json!({"format_version":"1.10","minecraft:item":{"description":{"identifier":"azur:berry","category":"Nature"},"components":{"minecraft:icon":{"texture":"azur_berry"}}}}).to_string(),
+        );
+        // Not an item at all, skipped with a warning
+        fs.insert("RP/items/notes.json", "// berries");
+
+        let mut addon = AddonParser::parse_addon_with(
+            fs.clone(),
+            "",
+            ParserConfig::builder()
+                .parse_block(true)
+                .parse_items(true)
+                .skip_bland(true)
+                .build(),
+        )?;
+        assert!(addon.items_ref()["azur:berry"].is_legacy());
+
+        addon.write()?;
+        let resource: Value = serde_json::from_slice(&fs.get("RP/items/food/berry.json").unwrap())?;
+        assert_eq!(
+            resource["minecraft:item"]["components"],
+            json!({"minecraft:icon":{"texture":"azur_berry"}})
+        );
+
+        addon.upgrade_legacy_items();
+        addon.write()?;
+        assert!(fs.get("RP/items/food/berry.json").is_none());
+        let item: Value = serde_json::from_slice(&fs.get("BP/items/berry.json").unwrap())?;
+        assert_eq!(item["format_version"], "1.21.40");
+        assert_eq!(
+            item["minecraft:item"]["components"]["minecraft:icon"],
+            "azur_berry"
+        );
+
+        Ok(())
+    }
 }
@@ -1 +1,2 @@
 pub mod addon_parser;
+pub mod path_filter;
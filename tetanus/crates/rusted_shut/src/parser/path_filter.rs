@@ -0,0 +1,81 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Decides which files the parser picks up, based on include and exclude glob lists.
+///
+/// Paths are matched relative to the pack base, so `blocks/wip/**` skips everything inside
+/// `BP/blocks/wip`. A `*` never crosses a `/`, use `**` for that.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// An empty `include` list accepts every path not matched by `exclude`.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, globset::Error> {
+        Ok(Self {
+            include: Self::build_set(include)?,
+            exclude: Self::build_set(exclude)?,
+        })
+    }
+
+    pub fn is_match<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+
+        if let Some(exclude) = &self.exclude {
+            if exclude.is_match(path) {
+                return false;
+            }
+        }
+
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.is_match(path))
+    }
+
+    fn build_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+        }
+
+        Ok(Some(builder.build()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::path_filter::PathFilter;
+
+    #[test]
+    fn include_exclude() -> Result<(), globset::Error> {
+        let everything = PathFilter::new(&[], &[])?;
+        assert!(everything.is_match("blocks/azur_lamp.json"));
+        assert!(everything.is_match("blocks/wip/azur_lamp.json"));
+
+        let no_wip = PathFilter::new(&[], &["blocks/wip/**".to_string()])?;
+        assert!(no_wip.is_match("blocks/azur_lamp.json"));
+        assert!(!no_wip.is_match("blocks/wip/azur_lamp.json"));
+        assert!(!no_wip.is_match("blocks/wip/deeper/azur_lamp.json"));
+
+        let only_azur = PathFilter::new(&["blocks/azur_*.json".to_string()], &[])?;
+        assert!(only_azur.is_match("blocks/azur_lamp.json"));
+        assert!(!only_azur.is_match("blocks/other_lamp.json"));
+        // `*` stays inside a single directory
+        assert!(!only_azur.is_match("blocks/azur_/lamp.json"));
+
+        let both = PathFilter::new(
+            &["blocks/**/azur_*.json".to_string()],
+            &["blocks/wip/**".to_string()],
+        )?;
+        assert!(both.is_match("blocks/decor/azur_lamp.json"));
+        assert!(!both.is_match("blocks/wip/azur_lamp.json"));
+
+        Ok(())
+    }
+}
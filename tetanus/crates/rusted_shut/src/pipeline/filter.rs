@@ -0,0 +1,31 @@
+use crate::addon::addon::Addon;
+use crate::addon::custom_infrastructure::processing_report::ProcessingReport;
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+
+/// What every filter of a pipeline gets to know about the workspace
+#[derive(Debug, Clone, Default)]
+pub struct FilterContext {
+    /// The folder the addon was parsed from
+    pub base_path: PathBuf,
+}
+
+/// One step of a [`crate::pipeline::runner::Pipeline`], transforming the whole addon.
+pub trait Filter: Sized {
+    /// Deserialized from the `config` of the filter's pipeline entry
+    type Config: DeserializeOwned;
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// The name the filter is listed under in the pipeline config
+    fn name() -> &'static str;
+
+    fn new(config: Self::Config, context: &FilterContext) -> Self;
+
+    fn run(&mut self, addon: Addon) -> Result<Addon, Self::Error>;
+
+    /// What the filter's [`crate::addon::custom_infrastructure::addon_processor::AddonProcessor`]
+    /// did during the last [`Filter::run`], for filters that use one
+    fn report(&self) -> Option<ProcessingReport> {
+        None
+    }
+}
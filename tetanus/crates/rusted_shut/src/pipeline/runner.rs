@@ -0,0 +1,195 @@
+use crate::addon::addon::Addon;
+use crate::addon::custom_infrastructure::processing_report::ProcessingReport;
+use crate::pipeline::filter::{Filter, FilterContext};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+
+fn empty_config() -> Value {
+    Value::Object(Default::default())
+}
+
+/// A filter to run, as listed in the pipeline config
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterEntry {
+    pub name: String,
+    #[serde(default = "empty_config")]
+    pub config: Value,
+}
+
+impl FilterEntry {
+    pub fn new<S: Into<String>>(name: S, config: Value) -> Self {
+        Self {
+            name: name.into(),
+            config,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("No filter is registered as {0}")]
+    UnknownFilter(String),
+    #[error("Invalid config for filter {0}: {1}")]
+    ConfigError(String, serde_json::Error),
+    #[error("Filter {0} failed: {1}")]
+    FilterError(String, Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// The processing report of one filter run, see [`Filter::report`]
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterReport {
+    pub filter: String,
+    pub report: ProcessingReport,
+}
+
+type FilterRunner = Box<
+    dyn Fn(
+        Value,
+        &FilterContext,
+        Addon,
+    ) -> Result<(Addon, Option<ProcessingReport>), PipelineError>,
+>;
+
+/// Runs filters by name, in the order the user lists them
+pub struct Pipeline {
+    filters: HashMap<&'static str, FilterRunner>,
+    context: FilterContext,
+}
+
+impl Pipeline {
+    pub fn new(context: FilterContext) -> Self {
+        Self {
+            filters: HashMap::new(),
+            context,
+        }
+    }
+
+    /// Makes `F` available under [`Filter::name`], replacing a filter registered with the same name
+    pub fn register<F: Filter + 'static>(&mut self) -> &mut Self {
+        self.filters.insert(
+            F::name(),
+            Box::new(|config, context, addon| {
+                let config = serde_json::from_value::<F::Config>(config)
+                    .map_err(|e| PipelineError::ConfigError(F::name().to_string(), e))?;
+
+                let mut filter = F::new(config, context);
+                let addon = filter
+                    .run(addon)
+                    .map_err(|e| PipelineError::FilterError(F::name().to_string(), Box::new(e)))?;
+                Ok((addon, filter.report()))
+            }),
+        );
+        self
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.filters.contains_key(name)
+    }
+
+    /// Runs `entries` one after the other, each getting the addon the previous one returned.
+    /// Every name is checked before the first filter runs.
+    pub fn run(&self, addon: Addon, entries: &[FilterEntry]) -> Result<Addon, PipelineError> {
+        self.run_reported(addon, entries).map(|(addon, _)| addon)
+    }
+
+    /// [`Pipeline::run`], also collecting the report of every filter which has one
+    pub fn run_reported(
+        &self,
+        mut addon: Addon,
+        entries: &[FilterEntry],
+    ) -> Result<(Addon, Vec<FilterReport>), PipelineError> {
+        if let Some(unknown) = entries.iter().find(|e| !self.is_registered(&e.name)) {
+            return Err(PipelineError::UnknownFilter(unknown.name.clone()));
+        }
+
+        let mut reports = vec![];
+        for entry in entries {
+            let report;
+            (addon, report) =
+                self.filters[entry.name.as_str()](entry.config.clone(), &self.context, addon)?;
+            reports.extend(report.map(|report| FilterReport {
+                filter: entry.name.clone(),
+                report,
+            }));
+        }
+
+        Ok((addon, reports))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::addon::addon::Addon;
+    use crate::addon::path_resolver::default_impl::BaseResolver;
+    use crate::pipeline::filter::{Filter, FilterContext};
+    use crate::pipeline::runner::{FilterEntry, Pipeline, PipelineError};
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::convert::Infallible;
+
+    #[derive(Deserialize)]
+    struct RenameConfig {
+        suffix: String,
+    }
+
+    /// Appends its suffix to the addon's name, tracked in the resolver's base path
+    struct Rename(RenameConfig);
+
+    impl Filter for Rename {
+        type Config = RenameConfig;
+        type Error = Infallible;
+
+        fn name() -> &'static str {
+            "rename"
+        }
+
+        fn new(config: Self::Config, _: &FilterContext) -> Self {
+            Self(config)
+        }
+
+        fn run(&mut self, mut addon: Addon) -> Result<Addon, Self::Error> {
+            let base = addon.resolver.get_behaviour_base();
+            let name = base.parent().unwrap().to_string_lossy().to_string();
+            addon.resolver = Box::new(BaseResolver::new(format!("{name}{}", self.0.suffix).into()));
+            Ok(addon)
+        }
+    }
+
+    #[test]
+    fn runs_in_listed_order() {
+        let mut pipeline = Pipeline::new(FilterContext::default());
+        pipeline.register::<Rename>();
+
+        let addon = Addon::new(BaseResolver::new("pack".into()));
+        let mut addon = pipeline
+            .run(
+                addon,
+                &[
+                    FilterEntry::new("rename", json!({"suffix": "_a"})),
+                    FilterEntry::new("rename", json!({"suffix": "_b"})),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            addon.resolver.get_behaviour_base(),
+            std::path::PathBuf::from("pack_a_b/BP")
+        );
+
+        let unknown = pipeline.run(
+            Addon::new(BaseResolver::new("pack".into())),
+            &[
+                FilterEntry::new("rename", json!({"suffix": "_a"})),
+                FilterEntry::new("office", json!({})),
+            ],
+        );
+        assert!(matches!(unknown, Err(PipelineError::UnknownFilter(name)) if name == "office"));
+
+        let bad_config = pipeline.run(
+            Addon::new(BaseResolver::new("pack".into())),
+            &[FilterEntry::new("rename", json!({}))],
+        );
+        assert!(matches!(bad_config, Err(PipelineError::ConfigError(name, _)) if name == "rename"));
+    }
+}
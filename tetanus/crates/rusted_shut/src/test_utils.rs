@@ -0,0 +1,72 @@
+use crate::addon::addon::Addon;
+use crate::addon::blocks::block::Block;
+use crate::addon::component::FormattedComponentRegister;
+use crate::addon::items::item::Item;
+use crate::addon::path_resolver::default_impl::BaseResolver;
+use crate::addon::traits::FormattedJsonSerialize;
+use crate::vfs::memory::MemoryFileSystem;
+use semver::Version;
+use serde_json::{json, Value};
+
+pub const FORMAT_VERSION: &str = "1.21.40";
+
+/// A block of [`FORMAT_VERSION`] from the contents of its `minecraft:block`
+pub fn block_from(block: Value) -> Block {
+    Block::from_json(
+        &json!({"format_version":FORMAT_VERSION,"minecraft:block":block}),
+        &FormattedComponentRegister::init_blocks(),
+        Version::new(0, 0, 0),
+    )
+    .unwrap()
+}
+
+pub fn block(id: &str, components: Value) -> Block {
+    block_from(json!({"description":{"identifier":id},"components":components}))
+}
+
+pub fn item(id: &str, components: Value) -> Item {
+    Item::from_json(
+        &json!({"format_version":FORMAT_VERSION,"minecraft:item":{"description":{"identifier":id},"components":components}}),
+        &FormattedComponentRegister::init_items(),
+        Version::new(0, 0, 0),
+    )
+    .unwrap()
+}
+
+/// An in memory addon holding `blocks`
+pub fn addon(blocks: impl IntoIterator<Item = Block>) -> Addon {
+    let mut addon = Addon::new(BaseResolver::with_file_system(
+        "./".into(),
+        MemoryFileSystem::new(),
+    ));
+    for block in blocks {
+        addon.push_block(block);
+    }
+    addon
+}
+
+/// Implements `CustomComponent` for a test component with a fixed id
+#[macro_export]
+macro_rules! test_component {
+    ($component:ty, $id:literal) => {
+        impl $crate::addon::custom_infrastructure::component::custom_base::CustomComponent
+            for $component
+        {
+            fn as_any(&self) -> &dyn ::std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+
+            fn id(&self) -> &str {
+                Self::static_id()
+            }
+
+            fn static_id() -> &'static str {
+                $id
+            }
+        }
+    };
+}
@@ -0,0 +1,358 @@
+use crate::vfs::file_system::FileSystem;
+use crate::vfs::memory::MemoryFileSystem;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    ZipError(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to read manifest {0}: {1}")]
+    ManifestError(PathBuf, serde_json::Error),
+    #[error("Archive does not contain a behaviour or resource pack")]
+    NoPacks,
+    #[error("Archive unpacks to more than {1} bytes, stopped at {0}")]
+    TooLarge(PathBuf, u64),
+}
+
+/// How many bytes an archive may unpack to, nested packs included
+pub const UNPACKED_LIMIT: u64 = 1 << 30;
+
+/// The pack folders of an unpacked archive, relative to its root and sorted by path
+#[derive(Debug, Default, Clone)]
+pub struct PackLayout {
+    pub behaviour: Vec<PathBuf>,
+    pub resource: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct ManifestModules {
+    #[serde(default)]
+    modules: Vec<ManifestModule>,
+}
+
+#[derive(Deserialize)]
+struct ManifestModule {
+    #[serde(rename = "type")]
+    module_type: String,
+}
+
+/// Unpacks a `.mcaddon` or `.mcpack` into memory.
+///
+/// A `.mcpack` is placed in a folder named after the file, so it looks like a `.mcaddon`
+/// holding that single pack.
+pub fn open_archive<P: AsRef<Path>>(path: P) -> Result<MemoryFileSystem, ArchiveError> {
+    let path = path.as_ref();
+    let fs = MemoryFileSystem::new();
+
+    let prefix = if path.extension() == Some(OsStr::new("mcpack")) {
+        PathBuf::from(path.file_stem().unwrap_or(OsStr::new("pack")))
+    } else {
+        PathBuf::new()
+    };
+
+    read_archive(std::fs::File::open(path)?, &prefix, &fs)?;
+    Ok(fs)
+}
+
+/// Copies every entry of the zip into `fs` below `prefix`. Nested `.mcpack` files, as found in
+/// most `.mcaddon`s, are unpacked into a folder named after them.
+///
+/// Fails once more than [`UNPACKED_LIMIT`] bytes were unpacked.
+pub fn read_archive<R: Read + Seek>(
+    reader: R,
+    prefix: &Path,
+    fs: &MemoryFileSystem,
+) -> Result<(), ArchiveError> {
+    let mut budget = UNPACKED_LIMIT;
+    read_limited(reader, prefix, fs, &mut budget, UNPACKED_LIMIT)
+}
+
+/// [`read_archive`] with `budget` bytes left of `limit`
+fn read_limited<R: Read + Seek>(
+    reader: R,
+    prefix: &Path,
+    fs: &MemoryFileSystem,
+    budget: &mut u64,
+    limit: u64,
+) -> Result<(), ArchiveError> {
+    let mut archive = ZipArchive::new(reader)?;
+
+    for idx in 0..archive.len() {
+        let mut entry = archive.by_index(idx)?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        // Entries escaping the archive root are skipped rather than trusted
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+
+        // The declared size isn't trusted, the read stops one byte past the budget instead
+        let mut data = Vec::new();
+        entry.by_ref().take(*budget + 1).read_to_end(&mut data)?;
+        *budget = budget
+            .checked_sub(data.len() as u64)
+            .ok_or_else(|| ArchiveError::TooLarge(prefix.join(&name), limit))?;
+
+        if name.extension() == Some(OsStr::new("mcpack")) {
+            let mut nested = prefix.join(&name);
+            nested.set_extension("");
+            read_limited(Cursor::new(data), &nested, fs, budget, limit)?;
+        } else {
+            fs.insert(prefix.join(name), data);
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the behaviour and resource packs by the module types in their `manifest.json`.
+pub fn detect_packs(fs: &MemoryFileSystem) -> Result<PackLayout, ArchiveError> {
+    let mut layout = PackLayout::default();
+
+    for path in fs.paths() {
+        if path.file_name() != Some(OsStr::new("manifest.json")) {
+            continue;
+        }
+
+        let manifest =
+            serde_json::from_slice::<ManifestModules>(&fs.get(&path).unwrap_or_default())
+                .map_err(|e| ArchiveError::ManifestError(path.clone(), e))?;
+        let folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        let has_module = |kind: &str| manifest.modules.iter().any(|e| e.module_type == kind);
+        if has_module("data") {
+            layout.behaviour.push(folder.clone());
+        }
+        if has_module("resources") {
+            layout.resource.push(folder);
+        }
+    }
+
+    if layout.behaviour.is_empty() && layout.resource.is_empty() {
+        return Err(ArchiveError::NoPacks);
+    }
+
+    Ok(layout)
+}
+
+/// Zips each of `packs` as a folder of the archive, named after the last part of its path.
+/// `outputs` replace the files at their paths, `None` leaving the file out.
+pub fn write_archive<W: Write + Seek>(
+    fs: &dyn FileSystem,
+    packs: &[PathBuf],
+    outputs: &HashMap<PathBuf, Option<Vec<u8>>>,
+    writer: W,
+) -> Result<W, ArchiveError> {
+    let mut zip = ZipWriter::new(writer);
+    let mut folders = HashSet::new();
+
+    for (idx, pack) in packs.iter().enumerate() {
+        let name = pack
+            .file_name()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("pack_{idx}"));
+        let mut folder = name.clone();
+        let mut count = 1;
+        while !folders.insert(folder.clone()) {
+            count += 1;
+            folder = format!("{name} ({count})");
+        }
+
+        let mut files = fs.walk_files(pack)?;
+        files.extend(
+            outputs
+                .keys()
+                .filter(|e| e.starts_with(pack) && !fs.exists(e))
+                .cloned(),
+        );
+        files.sort();
+
+        for file in files {
+            let data = match outputs.get(&file) {
+                Some(Some(data)) => data.clone(),
+                Some(None) => continue,
+                None => fs.read(&file)?,
+            };
+
+            let relative = file.strip_prefix(pack).unwrap_or(&file);
+            let name = relative.components().fold(folder.clone(), |acc, part| {
+                format!("{acc}/{}", part.as_os_str().to_string_lossy())
+            });
+
+            zip.start_file(name, SimpleFileOptions::default())?;
+            zip.write_all(&data)?;
+        }
+    }
+
+    Ok(zip.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::addon_parser::{AddonParser, ParserConfig};
+    use crate::vfs::archive::{read_archive, read_limited, ArchiveError};
+    use crate::vfs::memory::MemoryFileSystem;
+    use serde_json::{json, Value};
+    use std::io::{Cursor, Read, Write};
+    use std::path::Path;
+    use zip::write::SimpleFileOptions;
+    use zip::{ZipArchive, ZipWriter};
+
+    fn zip(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn mcaddon_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let bp = zip(&[
+            (
+                "manifest.json",
+                json!({"format_version": 2, "modules": [{"type": "data"}]})
+                    .to_string()
+                    .into_bytes(),
+            ),
+            (
+                "blocks/lamp.json",
+                json!({"format_version":"1.21.40","minecraft:block":{"description":{"identifier":"azur:lamp"},"components":{"azur:glow":{}}}})
+                    .to_string()
+                    .into_bytes(),
+            ),
+        ]);
+        let addon = zip(&[
+            ("Lamps BP.mcpack", bp),
+            (
+                "Lamps RP/manifest.json",
+                json!({"format_version": 2, "modules": [{"type": "resources"}]})
+                    .to_string()
+                    .into_bytes(),
+            ),
+            ("Lamps RP/textures/lamp.png", vec![0x89, 0x50, 0x4e, 0x47]),
+            (
+                "extra/Lamps RP/manifest.json",
+                json!({"format_version": 2, "modules": [{"type": "resources"}]})
+                    .to_string()
+                    .into_bytes(),
+            ),
+        ]);
+
+        let fs = MemoryFileSystem::new();
+        read_archive(Cursor::new(addon), Path::new(""), &fs)?;
+
+        let mut addon = AddonParser::parse_archive_file_system(
+            fs,
+            ParserConfig::builder()
+                .parse_block(true)
+                .parse_items(true)
+                .skip_bland(true)
+                .build(),
+        )?;
+        assert!(addon.blocks_ref().contains_key("azur:lamp"));
+
+        addon
+            .blocks_mut_ref()
+            .get_mut("azur:lamp")
+            .unwrap()
+            .components
+            .remove_component("azur:glow");
+
+        let written = addon.write_archive(Cursor::new(Vec::new()))?;
+        let mut archive = ZipArchive::new(Cursor::new(written.into_inner()))?;
+
+        let mut names = archive.file_names().map(str::to_string).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "Lamps BP/blocks/lamp.json",
+                "Lamps BP/manifest.json",
+                "Lamps RP (2)/manifest.json",
+                "Lamps RP/manifest.json",
+                "Lamps RP/textures/lamp.png",
+            ]
+        );
+
+        let mut block = String::new();
+        archive
+            .by_name("Lamps BP/blocks/lamp.json")?
+            .read_to_string(&mut block)?;
+        let block: Value = serde_json::from_str(&block)?;
+        assert_eq!(
+            block["minecraft:block"]["components"],
+            json!({}),
+            "processed block should replace the original"
+        );
+
+        let source: Value = serde_json::from_str(
+            &addon
+                .resolver
+                .file_system()
+                .read_to_string(Path::new("Lamps BP/blocks/lamp.json"))?,
+        )?;
+        assert_eq!(
+            source["minecraft:block"]["components"],
+            json!({"azur:glow": {}}),
+            "the source pack should be left alone"
+        );
+
+        let mut texture = Vec::new();
+        archive
+            .by_name("Lamps RP/textures/lamp.png")?
+            .read_to_end(&mut texture)?;
+        assert_eq!(texture, vec![0x89, 0x50, 0x4e, 0x47]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unpacked_limit() {
+        let pack = zip(&[
+            ("blocks/a.json", vec![b' '; 60]),
+            ("blocks/b.json", vec![b' '; 60]),
+        ]);
+        // The nested pack counts as well as what it unpacks to
+        let limit = pack.len() as u64 + 100;
+        let addon = zip(&[("Lamps BP.mcpack", pack)]);
+
+        let fs = MemoryFileSystem::new();
+        let result = read_limited(
+            Cursor::new(&addon),
+            Path::new(""),
+            &fs,
+            &mut { limit },
+            limit,
+        );
+        assert!(matches!(
+            result,
+            Err(ArchiveError::TooLarge(path, l)) if l == limit && path == Path::new("Lamps BP/blocks/b.json")
+        ));
+        assert_eq!(fs.paths().len(), 1);
+
+        let fs = MemoryFileSystem::new();
+        let limit = limit + 20;
+        read_limited(
+            Cursor::new(&addon),
+            Path::new(""),
+            &fs,
+            &mut { limit },
+            limit,
+        )
+        .unwrap();
+        assert_eq!(fs.paths().len(), 2);
+    }
+}
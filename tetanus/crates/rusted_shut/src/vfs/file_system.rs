@@ -0,0 +1,19 @@
+use std::fmt::Debug;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Storage backing an addon. Everything the parser reads and `Addon::write` writes goes through
+/// this, so the whole pipeline can run against disk or purely in memory.
+pub trait FileSystem: Debug {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    fn exists(&self, path: &Path) -> bool;
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Recursively lists every file below `base`. Returned paths start with `base`.
+    fn walk_files(&self, base: &Path) -> io::Result<Vec<PathBuf>>;
+}
@@ -0,0 +1,163 @@
+use crate::vfs::file_system::FileSystem;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+/// Keeps every file in memory. Clones share the same files, so a caller can keep a handle to
+/// inspect what a parser or writer did with the copy it was given.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFileSystem {
+    files: Rc<RefCell<BTreeMap<PathBuf, Vec<u8>>>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn insert<P: AsRef<Path>, C: Into<Vec<u8>>>(&self, path: P, contents: C) {
+        self.files
+            .borrow_mut()
+            .insert(Self::normalize(path.as_ref()), contents.into());
+    }
+
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<Vec<u8>> {
+        self.files
+            .borrow()
+            .get(&Self::normalize(path.as_ref()))
+            .cloned()
+    }
+
+    pub fn remove<P: AsRef<Path>>(&self, path: P) -> Option<Vec<u8>> {
+        self.files
+            .borrow_mut()
+            .remove(&Self::normalize(path.as_ref()))
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.borrow().keys().cloned().collect()
+    }
+
+    /// Lexically resolves `.` and `..` so `./BP/blocks/a.json` and `BP/blocks/a.json` are the same file
+    fn normalize(path: &Path) -> PathBuf {
+        let mut out = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    out.pop();
+                }
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} does not exist in memory", path.display()),
+        )
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.get(path).ok_or_else(|| Self::not_found(path))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.insert(path, contents);
+        Ok(())
+    }
+
+    /// Files and the directories holding them. Paths are compared by component, so `BP/blocks`
+    /// doesn't exist through `BP/blocks_extra/a.json`.
+    fn exists(&self, path: &Path) -> bool {
+        let path = Self::normalize(path);
+        self.files
+            .borrow()
+            .keys()
+            .any(|file| file.starts_with(&path))
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        MemoryFileSystem::remove(self, path)
+            .map(|_| ())
+            .ok_or_else(|| Self::not_found(path))
+    }
+
+    fn walk_files(&self, base: &Path) -> io::Result<Vec<PathBuf>> {
+        let normalized = Self::normalize(base);
+        Ok(self
+            .files
+            .borrow()
+            .keys()
+            .filter_map(|file| file.strip_prefix(&normalized).ok())
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map(|relative| base.join(relative))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::addon::traits::FormattedJsonSerialize;
+    use crate::parser::addon_parser::{AddonParser, ParserConfig};
+    use crate::vfs::file_system::FileSystem;
+    use crate::vfs::memory::MemoryFileSystem;
+    use serde_json::{json, Value};
+    use std::path::Path;
+
+    #[test]
+    fn directories_exist() {
+        let fs = MemoryFileSystem::new();
+        fs.insert("BP/blocks_extra/lamp.json", "{}");
+
+        assert!(fs.exists(Path::new("BP/blocks_extra/lamp.json")));
+        assert!(fs.exists(Path::new("./BP/blocks_extra")));
+        assert!(fs.exists(Path::new("BP")));
+        assert!(!fs.exists(Path::new("BP/blocks")));
+        assert!(!fs.exists(Path::new("BP/blocks_extra/lamp")));
+        assert!(fs.walk_files(Path::new("BP/blocks")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_and_write_in_memory() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        fs.insert(
+            "pack/BP/blocks/lamp.json",
+            json!({"format_version":"1.21.40","minecraft:block":{"description":{"identifier":"azur:lamp"},"components":{"azur:glow":{}}}}).to_string(),
+        );
+        fs.insert("pack/BP/blocks/notes.txt", "not a block");
+
+        let mut addon = AddonParser::parse_addon_with(
+            fs.clone(),
+            "./pack",
+            ParserConfig::builder()
+                .parse_block(true)
+                .parse_items(true)
+                .skip_bland(true)
+                .build(),
+        )?;
+
+        assert_eq!(addon.blocks_ref().len(), 1);
+        addon
+            .blocks_mut_ref()
+            .get_mut("azur:lamp")
+            .unwrap()
+            .components
+            .remove_component("azur:glow");
+        let expected = addon.blocks_ref()["azur:lamp"].to_json();
+
+        addon.write()?;
+
+        let written: Value = serde_json::from_slice(&fs.get("pack/BP/blocks/lamp.json").unwrap())?;
+        assert_eq!(written, expected);
+        assert_eq!(fs.paths().len(), 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod archive;
+pub mod disk;
+pub mod file_system;
+pub mod memory;
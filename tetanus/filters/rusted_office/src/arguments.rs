@@ -0,0 +1,455 @@
+use serde_json::{Map, Number, Value};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+/// A 1 based line and column, the column counted in characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// The position of the byte `offset` in `text`, clamped to a character boundary
+    pub fn of(text: &str, offset: usize) -> Self {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |e| e + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ArgumentError {
+    #[error("unterminated string starting at {0}")]
+    UnterminatedString(Position),
+    #[error("invalid escape \\{1} at {0}")]
+    InvalidEscape(Position, char),
+    #[error("expected {expected} at {position}, found {found}")]
+    Unexpected {
+        position: Position,
+        expected: &'static str,
+        found: String,
+    },
+    #[error("duplicate argument {1} at {0}")]
+    Duplicate(Position, String),
+}
+
+/// A single `name: value` of a component's metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    pub name: String,
+    pub value: Value,
+    /// Where the value starts in its source file
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+    Colon,
+    Comma,
+    Str(String),
+    /// Anything unquoted, like `true`, `1.5` or `azur`
+    Word(String),
+    End,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::OpenBrace => write!(f, "'{{'"),
+            TokenKind::CloseBrace => write!(f, "'}}'"),
+            TokenKind::OpenBracket => write!(f, "'['"),
+            TokenKind::CloseBracket => write!(f, "']'"),
+            TokenKind::OpenParen => write!(f, "'('"),
+            TokenKind::CloseParen => write!(f, "')'"),
+            TokenKind::Colon => write!(f, "':'"),
+            TokenKind::Comma => write!(f, "','"),
+            TokenKind::Str(str) => write!(f, "{str:?}"),
+            TokenKind::Word(word) => write!(f, "{word}"),
+            TokenKind::End => write!(f, "the end"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Byte range in the lexed text
+    start: usize,
+    end: usize,
+}
+
+fn is_punctuation(char: char) -> bool {
+    matches!(
+        char,
+        '{' | '}' | '[' | ']' | '(' | ')' | ':' | ',' | '"' | '\''
+    )
+}
+
+/// Splits text into tokens on demand, so anything after the arguments is never looked at
+struct Lexer<'a> {
+    text: &'a str,
+    offset: usize,
+    locate: &'a dyn Fn(usize) -> Position,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek_char(&self) -> Option<char> {
+        self.text[self.offset..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let char = self.peek_char()?;
+        self.offset += char.len_utf8();
+        Some(char)
+    }
+
+    fn next_token(&mut self) -> Result<Token, ArgumentError> {
+        while self.peek_char().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+
+        let start = self.offset;
+        let Some(char) = self.bump() else {
+            return Ok(Token {
+                kind: TokenKind::End,
+                start,
+                end: start,
+            });
+        };
+
+        let kind = match char {
+            '{' => TokenKind::OpenBrace,
+            '}' => TokenKind::CloseBrace,
+            '[' => TokenKind::OpenBracket,
+            ']' => TokenKind::CloseBracket,
+            '(' => TokenKind::OpenParen,
+            ')' => TokenKind::CloseParen,
+            ':' => TokenKind::Colon,
+            ',' => TokenKind::Comma,
+            '"' | '\'' => TokenKind::Str(self.string(char, start)?),
+            _ => {
+                while self
+                    .peek_char()
+                    .is_some_and(|e| !e.is_whitespace() && !is_punctuation(e))
+                {
+                    self.bump();
+                }
+                TokenKind::Word(self.text[start..self.offset].to_string())
+            }
+        };
+
+        Ok(Token {
+            kind,
+            start,
+            end: self.offset,
+        })
+    }
+
+    fn string(&mut self, quote: char, start: usize) -> Result<String, ArgumentError> {
+        let mut out = String::new();
+        loop {
+            let escape_start = self.offset;
+            match self.bump() {
+                None => return Err(ArgumentError::UnterminatedString((self.locate)(start))),
+                Some(char) if char == quote => return Ok(out),
+                Some('\\') => {
+                    let invalid =
+                        |char| ArgumentError::InvalidEscape((self.locate)(escape_start), char);
+                    let escaped = match self.bump() {
+                        None => {
+                            return Err(ArgumentError::UnterminatedString((self.locate)(start)))
+                        }
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('0') => '\0',
+                        Some('u') => self.unicode_escape().ok_or_else(|| invalid('u'))?,
+                        Some(char @ ('\\' | '/' | '"' | '\'')) => char,
+                        Some(char) => return Err(invalid(char)),
+                    };
+                    out.push(escaped);
+                }
+                Some(char) => out.push(char),
+            }
+        }
+    }
+
+    /// The rest of a `\uXXXX` or `\u{X...}` escape
+    fn unicode_escape(&mut self) -> Option<char> {
+        let digits = if self.peek_char() == Some('{') {
+            self.bump();
+            let end = self.text[self.offset..].find('}')?;
+            let digits = &self.text[self.offset..self.offset + end];
+            self.offset += end + 1;
+            digits
+        } else {
+            let digits = self.text[self.offset..].get(..4)?;
+            self.offset += 4;
+            digits
+        };
+        char::from_u32(u32::from_str_radix(digits, 16).ok()?)
+    }
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Token>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Result<&Token, ArgumentError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lexer.next_token()?);
+        }
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    fn next(&mut self) -> Result<Token, ArgumentError> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.lexer.next_token(),
+        }
+    }
+
+    fn unexpected(&self, token: &Token, expected: &'static str) -> ArgumentError {
+        ArgumentError::Unexpected {
+            position: (self.lexer.locate)(token.start),
+            expected,
+            found: token.kind.to_string(),
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &'static str) -> Result<Token, ArgumentError> {
+        let token = self.next()?;
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(self.unexpected(&token, expected))
+        }
+    }
+
+    /// `name: value` pairs separated by commas, up to and including `close`
+    fn members(&mut self, close: TokenKind) -> Result<Vec<Argument>, ArgumentError> {
+        let mut out: Vec<Argument> = Vec::new();
+        loop {
+            let token = self.next()?;
+            let name = match token.kind {
+                ref kind if *kind == close => return Ok(out),
+                TokenKind::Str(name) | TokenKind::Word(name) => name,
+                _ => return Err(self.unexpected(&token, "an argument name")),
+            };
+            self.expect(TokenKind::Colon, "':'")?;
+
+            let position = (self.lexer.locate)(self.peek()?.start);
+            let value = self.value()?;
+            if out.iter().any(|e| e.name == name) {
+                return Err(ArgumentError::Duplicate(
+                    (self.lexer.locate)(token.start),
+                    name,
+                ));
+            }
+            out.push(Argument {
+                name,
+                value,
+                position,
+            });
+
+            let token = self.next()?;
+            match token.kind {
+                TokenKind::Comma => {}
+                ref kind if *kind == close => return Ok(out),
+                _ => return Err(self.unexpected(&token, "',' or the end of the arguments")),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ArgumentError> {
+        let token = self.next()?;
+        Ok(match token.kind {
+            TokenKind::Str(str) => Value::String(str),
+            TokenKind::Word(word) => self.bare(word, token.end)?,
+            TokenKind::OpenBrace => Value::Object(
+                self.members(TokenKind::CloseBrace)?
+                    .into_iter()
+                    .map(|e| (e.name, e.value))
+                    .collect::<Map<_, _>>(),
+            ),
+            TokenKind::OpenBracket => {
+                let mut out = Vec::new();
+                loop {
+                    if self.peek()?.kind == TokenKind::CloseBracket {
+                        self.next()?;
+                        break;
+                    }
+                    out.push(self.value()?);
+
+                    let token = self.next()?;
+                    match token.kind {
+                        TokenKind::Comma => {}
+                        TokenKind::CloseBracket => break,
+                        _ => return Err(self.unexpected(&token, "',' or ']'")),
+                    }
+                }
+                Value::Array(out)
+            }
+            _ => return Err(self.unexpected(&token, "a value")),
+        })
+    }
+
+    /// An unquoted value. Words and colons written without spaces between them, like
+    /// `azur:glow`, form a single string.
+    fn bare(&mut self, word: String, mut end: usize) -> Result<Value, ArgumentError> {
+        let mut joined = word.clone();
+        loop {
+            let token = self.peek()?;
+            if token.start != end || !matches!(token.kind, TokenKind::Colon | TokenKind::Word(_)) {
+                break;
+            }
+            let token = self.next()?;
+            end = token.end;
+            joined.push_str(&self.lexer.text[token.start..token.end]);
+        }
+
+        if joined != word {
+            return Ok(Value::String(joined));
+        }
+        Ok(match word.as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "null" => Value::Null,
+            _ => match word.parse::<f64>() {
+                Ok(num) if num.is_finite() => number(num),
+                _ => Value::String(word),
+            },
+        })
+    }
+}
+
+/// Whole numbers stay integers, so `1` doesn't turn into `1.0`
+pub fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Value::from(value as i64)
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// Parses the parenthesized arguments at the start of `text`, like `(id: azur:glow, type: block)`
+/// or `({ id: "azur:glow", type: "block" })`. Text after the closing parenthesis is ignored.
+/// `locate` turns a byte offset into `text` into a position in its source file.
+pub fn parse_arguments(
+    text: &str,
+    locate: &dyn Fn(usize) -> Position,
+) -> Result<Vec<Argument>, ArgumentError> {
+    let mut parser = Parser {
+        lexer: Lexer {
+            text,
+            offset: 0,
+            locate,
+        },
+        peeked: None,
+    };
+
+    parser.expect(TokenKind::OpenParen, "'('")?;
+    if parser.peek()?.kind != TokenKind::OpenBrace {
+        return parser.members(TokenKind::CloseParen);
+    }
+
+    parser.next()?;
+    let arguments = parser.members(TokenKind::CloseBrace)?;
+    if parser.peek()?.kind == TokenKind::Comma {
+        parser.next()?;
+    }
+    parser.expect(TokenKind::CloseParen, "')'")?;
+    Ok(arguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arguments::{parse_arguments, ArgumentError, Position};
+    use serde_json::json;
+
+    fn parse(text: &str) -> Result<Vec<(String, serde_json::Value)>, ArgumentError> {
+        parse_arguments(text, &|offset| Position::of(text, offset))
+            .map(|e| e.into_iter().map(|e| (e.name, e.value)).collect())
+    }
+
+    #[test]
+    fn parses_values() {
+        assert_eq!(
+            parse("(id: azur:glow, type: block, pureData: true) class Glow {}").unwrap(),
+            vec![
+                ("id".to_string(), json!("azur:glow")),
+                ("type".to_string(), json!("block")),
+                ("pureData".to_string(), json!(true)),
+            ]
+        );
+        assert_eq!(
+            parse(
+                r#"({ id: "azur:a, b: c", 'scale': [1, -2.5, "é\n"], data: { ünï: 'çödé\'s' }, })"#
+            )
+            .unwrap(),
+            vec![
+                ("id".to_string(), json!("azur:a, b: c")),
+                ("scale".to_string(), json!([1, -2.5, "é\n"])),
+                ("data".to_string(), json!({"ünï": "çödé's"})),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_positions() {
+        assert_eq!(
+            parse("(id: \"é\\q\")"),
+            Err(ArgumentError::InvalidEscape(
+                Position { line: 1, column: 8 },
+                'q'
+            ))
+        );
+        assert_eq!(
+            parse("(\n  id: \"ünï,\n)"),
+            Err(ArgumentError::UnterminatedString(Position {
+                line: 2,
+                column: 7
+            }))
+        );
+        assert_eq!(
+            parse("(id: [1 2])"),
+            Err(ArgumentError::Unexpected {
+                position: Position { line: 1, column: 9 },
+                expected: "',' or ']'",
+                found: "2".to_string()
+            })
+        );
+        assert_eq!(
+            parse("(id: a, id: b)"),
+            Err(ArgumentError::Duplicate(
+                Position { line: 1, column: 9 },
+                "id".to_string()
+            ))
+        );
+    }
+}
@@ -1,4 +1,7 @@
+use crate::arguments::{Argument, Position};
+use crate::schema::ParamSchema;
 use bon::Builder;
+use serde_json::Value;
 use std::collections::hash_map::Values;
 use std::collections::HashMap;
 use std::path::Path;
@@ -13,6 +16,20 @@ pub enum ComponentInformationError {
     InvalidComponentType(String),
     #[error("Missing Required Param: {0}, type: {1}, class: {2}")]
     MissingRequiredParam(&'static str, &'static str, String),
+    #[error("Invalid value for param {name} of {class} at {position}: expected {expected}, found {found}")]
+    InvalidParamValue {
+        name: String,
+        expected: &'static str,
+        found: Value,
+        position: Position,
+        class: String,
+    },
+    #[error("Invalid schema of {class} at {position}: {reason}")]
+    InvalidSchema {
+        reason: String,
+        position: Position,
+        class: String,
+    },
 }
 
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -42,6 +59,9 @@ impl FromStr for ComponentType {
 pub struct ComponentStaticInformation {
     pub class_id: String,
     pub relative_path: Box<Path>,
+    /// The class is the file's default export
+    #[builder(default)]
+    pub default_export: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -50,12 +70,14 @@ pub struct ComponentInformation {
     pub component_type: ComponentType,
     pub is_pure_data: bool,
     pub pass_id: bool,
+    /// What instances must pass, from the `schema` argument or the constructor's parameter type
+    pub schema: Option<ParamSchema>,
     pub information: ComponentStaticInformation,
 }
 
 impl ComponentInformation {
     pub fn new(
-        arg_list: Vec<(&str, &str)>,
+        arg_list: Vec<Argument>,
         information: ComponentStaticInformation,
     ) -> Result<Self, ComponentInformationError> {
         let mut self_data = Self {
@@ -63,17 +85,36 @@ impl ComponentInformation {
             component_type: ComponentType::Invalid,
             is_pure_data: false,
             pass_id: false,
+            schema: None,
             information,
         };
 
-        for (name, val) in arg_list {
-            match name {
-                "id" => self_data.search_id = val.to_string(),
-                "type" => self_data.component_type = val.parse()?,
-                "pureData" => self_data.is_pure_data = bool::from_str(val).unwrap_or(false),
-                "passId" => self_data.pass_id = bool::from_str(val).unwrap_or(false),
-                v => {
-                    log::warn!("Unknown param: name: {}, value: {}", v, val)
+        for arg in arg_list {
+            let invalid = |expected| ComponentInformationError::InvalidParamValue {
+                name: arg.name.clone(),
+                expected,
+                found: arg.value.clone(),
+                position: arg.position,
+                class: self_data.information.class_id.clone(),
+            };
+            match (arg.name.as_str(), &arg.value) {
+                ("id", Value::String(val)) => self_data.search_id = val.clone(),
+                ("type", Value::String(val)) => self_data.component_type = val.parse()?,
+                ("pureData", Value::Bool(val)) => self_data.is_pure_data = *val,
+                ("passId", Value::Bool(val)) => self_data.pass_id = *val,
+                ("schema", val) => {
+                    self_data.schema = Some(ParamSchema::from_value(val).map_err(|reason| {
+                        ComponentInformationError::InvalidSchema {
+                            reason,
+                            position: arg.position,
+                            class: self_data.information.class_id.clone(),
+                        }
+                    })?)
+                }
+                ("id" | "type", _) => return Err(invalid("a string")),
+                ("pureData" | "passId", _) => return Err(invalid("a boolean")),
+                (name, val) => {
+                    log::warn!("Unknown param: name: {}, value: {}", name, val)
                 }
             }
         }
@@ -153,6 +194,14 @@ impl CustomComponentRegistry {
         self.item_list.values()
     }
 
+    pub fn block_information(&self, component_id: &str) -> Option<&Rc<ComponentInformation>> {
+        self.block_list.get(component_id)
+    }
+
+    pub fn item_information(&self, component_id: &str) -> Option<&Rc<ComponentInformation>> {
+        self.item_list.get(component_id)
+    }
+
     pub fn block_instances_iter(&self) -> Iter<'_, ComponentInstance> {
         self.block_instances.iter()
     }
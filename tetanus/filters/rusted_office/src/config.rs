@@ -11,6 +11,7 @@ pub enum ParserMode {
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
 pub struct ExposedOfficeConfig {
     pub mode: ParserMode,
     pub type_check: bool,
@@ -31,3 +32,23 @@ pub struct OfficeConfig {
     pub script_entry: String,
     pub script_search_location: Box<Path>,
 }
+
+fn default_script_path() -> String {
+    "data/gametests/src".to_string()
+}
+
+fn default_main_file() -> String {
+    "main".to_string()
+}
+
+/// The config of the `office` pipeline filter. Paths are relative to the addon's base path.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OfficeFilterConfig {
+    #[serde(default = "default_script_path")]
+    pub script_path: String,
+    /// The entry script, without the `.ts` extension
+    #[serde(default = "default_main_file")]
+    pub main_file: String,
+    #[serde(flatten)]
+    pub parsed_config: ExposedOfficeConfig,
+}
@@ -1,10 +1,13 @@
-use crate::config::OfficeConfig;
+use crate::config::{OfficeConfig, OfficeFilterConfig};
+use crate::schema::TypeCheckError;
 use crate::stages::ast_stage::{ASTError, ASTStage};
 use crate::stages::component_rip_stage::ComponentRipperError;
 use crate::stages::emitter::CodeEmitter;
 use crate::stages::json_application_stage::{register_component_types, BlockError, ItemError};
 use rusted_shut::addon::addon::Addon;
 use rusted_shut::addon::custom_infrastructure::addon_processor::ProcessingError;
+use rusted_shut::addon::custom_infrastructure::processing_report::ProcessingReport;
+use rusted_shut::pipeline::filter::{Filter, FilterContext};
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -18,6 +21,8 @@ pub enum OfficeError {
     ProcessingError(#[from] ProcessingError<BlockError, ItemError>),
     #[error(transparent)]
     FileError(#[from] std::io::Error),
+    #[error(transparent)]
+    TypeCheck(#[from] TypeCheckError),
 }
 
 #[derive(Debug)]
@@ -33,49 +38,60 @@ impl RustedOffice {
     }
 
     pub fn process(self, addon: Addon) -> Result<Addon, OfficeError> {
+        self.process_reported(addon).map(|(addon, _)| addon)
+    }
+
+    /// [`RustedOffice::process`], also returning the processor's report if any components were
+    /// found
+    pub fn process_reported(
+        self,
+        addon: Addon,
+    ) -> Result<(Addon, Option<ProcessingReport>), OfficeError> {
         let ast = self.next_stage();
 
         let func_ripper = ast.next_state()?;
         let (funcs, ripper) = func_ripper.next_stage();
         let registry = ripper.build_registry()?;
         if let None = registry {
-            return Ok(addon);
+            return Ok((addon, None));
         }
 
         let mut processor = register_component_types(registry.unwrap());
-        let addon = processor.process_addon(addon)?;
+        processor.state_mut().type_check = self.config.parsed_config.type_check;
+        let mut addon = processor.process_addon(addon)?;
+
+        let type_errors = std::mem::take(&mut processor.state_mut().type_errors);
+        if !type_errors.is_empty() {
+            return Err(TypeCheckError(type_errors).into());
+        }
+        let report = processor.take_report();
         let registry = processor.disband().registry;
 
         let emitter = CodeEmitter::new(registry, funcs);
 
         let source = emitter.emit();
 
-        std::fs::write(
-            {
-                let mut pth = PathBuf::from(self.config.script_search_location.clone());
-                pth.push("rusted_office_register.ts");
-                pth
-            },
-            source,
-        )?;
-
-        std::fs::write(
-            {
-                let mut pth = PathBuf::from(self.config.script_search_location.clone());
-                pth.push(self.config.script_entry.clone());
-                pth
-            },
+        // Written with the addon, so a dry run only diffs them. The paths are made absolute while
+        // still in the working directory the scripts were found from.
+        let mut register = PathBuf::from(self.config.script_search_location.clone());
+        register.push("rusted_office_register.ts");
+        addon.push_extra_file(std::path::absolute(register)?, source);
+
+        let mut entry = PathBuf::from(self.config.script_search_location);
+        entry.push(self.config.script_entry);
+        let entry = std::path::absolute(entry)?;
+        let script = match addon.extra_file(&entry) {
+            Some(script) => script.clone(),
+            None => std::fs::read_to_string(&entry)?,
+        };
+        addon.push_extra_file(
+            entry,
             format!(
-                "import {{initOfficeComponents}} from \"./rusted_office_register\"; import {{world as MyCustomRegister}} from \"@minecraft/server\";MyCustomRegister.beforeEvents.worldInitialize.subscribe(initOfficeComponents); \n{}",
-                std::fs::read_to_string({
-                    let mut pth = PathBuf::from(self.config.script_search_location);
-                    pth.push(self.config.script_entry);
-                    pth
-                })?
+                "import {{initOfficeComponents}} from \"./rusted_office_register\"; import {{world as MyCustomRegister}} from \"@minecraft/server\";MyCustomRegister.beforeEvents.worldInitialize.subscribe(initOfficeComponents); \n{script}"
             ),
-        )?;
+        );
 
-        Ok(addon)
+        Ok((addon, Some(report)))
     }
 
     fn next_stage(&self) -> ASTStage {
@@ -89,3 +105,51 @@ impl RustedOffice {
         self.next_stage()
     }
 }
+
+/// Runs [`RustedOffice`] from inside the addon's base path
+#[derive(Debug)]
+pub struct OfficeFilter {
+    pub config: OfficeConfig,
+    pub base_path: PathBuf,
+    report: Option<ProcessingReport>,
+}
+
+impl Filter for OfficeFilter {
+    type Config = OfficeFilterConfig;
+    type Error = OfficeError;
+
+    fn name() -> &'static str {
+        "office"
+    }
+
+    fn new(config: Self::Config, context: &FilterContext) -> Self {
+        let mut script_search_location = context.base_path.clone();
+        script_search_location.push(&config.script_path);
+
+        Self {
+            config: OfficeConfig {
+                parsed_config: config.parsed_config,
+                script_entry: format!("{}.{}", config.main_file, "ts"),
+                script_search_location: script_search_location.into_boxed_path(),
+            },
+            base_path: context.base_path.clone(),
+            report: None,
+        }
+    }
+
+    fn run(&mut self, addon: Addon) -> Result<Addon, Self::Error> {
+        let old_wd = std::env::current_dir()?;
+        std::env::set_current_dir(self.base_path.canonicalize()?)?;
+
+        let result = RustedOffice::new(self.config.clone()).process_reported(addon);
+
+        std::env::set_current_dir(&old_wd)?;
+        let (addon, report) = result?;
+        self.report = report;
+        Ok(addon)
+    }
+
+    fn report(&self) -> Option<ProcessingReport> {
+        self.report.clone()
+    }
+}
@@ -1,8 +1,10 @@
 #![allow(dead_code)]
+pub mod arguments;
 pub mod ast_utils;
 pub mod component_registry;
 pub mod config;
 pub mod filter;
 mod function_store;
+pub mod schema;
 pub mod stages;
 pub mod string_extensions;
@@ -0,0 +1,529 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use swc_ecma_ast::{
+    BindingIdent, ClassDecl, ClassMember, Expr, Lit, Module, ParamOrTsParamProp, Pat, TsEntityName,
+    TsKeywordTypeKind, TsLit, TsParamPropParam, TsType, TsTypeElement, TsTypeLit, TsTypeOperatorOp,
+    TsUnionOrIntersectionType,
+};
+use swc_ecma_visit::{Visit, VisitWith};
+use thiserror::Error;
+
+/// The shape of the data a component is constructed with
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamSchema {
+    Any,
+    String,
+    Number,
+    Boolean,
+    Null,
+    /// Only matches a missing object field
+    Undefined,
+    Literal(Value),
+    Array(Box<ParamSchema>),
+    Tuple(Vec<ParamSchema>),
+    Object {
+        fields: BTreeMap<String, SchemaField>,
+        /// The schema of fields not in `fields`, `None` if there may be none
+        rest: Option<Box<ParamSchema>>,
+    },
+    Union(Vec<ParamSchema>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaField {
+    pub schema: ParamSchema,
+    pub optional: bool,
+}
+
+impl Display for ParamSchema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamSchema::Any => write!(f, "any"),
+            ParamSchema::String => write!(f, "string"),
+            ParamSchema::Number => write!(f, "number"),
+            ParamSchema::Boolean => write!(f, "boolean"),
+            ParamSchema::Null => write!(f, "null"),
+            ParamSchema::Undefined => write!(f, "undefined"),
+            ParamSchema::Literal(value) => write!(f, "{value}"),
+            ParamSchema::Array(schema) => match schema.as_ref() {
+                ParamSchema::Union(_) => write!(f, "({schema})[]"),
+                schema => write!(f, "{schema}[]"),
+            },
+            ParamSchema::Tuple(schemas) => {
+                let schemas = schemas.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "[{}]", schemas.join(", "))
+            }
+            ParamSchema::Object { fields, rest } => {
+                let mut members = fields
+                    .iter()
+                    .map(|(name, field)| {
+                        let optional = if field.optional { "?" } else { "" };
+                        format!("{name}{optional}: {}", field.schema)
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(rest) = rest {
+                    members.push(format!("[key: string]: {rest}"));
+                }
+                write!(f, "{{ {} }}", members.join(", "))
+            }
+            ParamSchema::Union(schemas) => {
+                let schemas = schemas.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", schemas.join(" | "))
+            }
+        }
+    }
+}
+
+/// A value not matching its [`ParamSchema`], at a path like `$.color[0]`
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SchemaViolation {
+    #[error("{path}: expected {expected}, found {found}")]
+    Mismatch {
+        path: String,
+        expected: String,
+        found: Value,
+    },
+    #[error("{path}: missing, expected {expected}")]
+    Missing { path: String, expected: String },
+    #[error("{path}: unknown field")]
+    Unknown { path: String },
+}
+
+impl ParamSchema {
+    /// Reads an explicit schema from `@Generate`, like
+    /// `{ radius: "number", "color?": "string", tags: ["string"] }`.
+    ///
+    /// Strings name a type, `[]` after it makes an array and `|` separates union members. An
+    /// array of one schema is an array of it, any other array a tuple. Object keys ending in `?`
+    /// are optional.
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        Ok(match value {
+            Value::String(name) => Self::from_name(name)?,
+            Value::Array(schemas) if schemas.len() == 1 => {
+                Self::Array(Box::new(Self::from_value(&schemas[0])?))
+            }
+            Value::Array(schemas) => Self::Tuple(
+                schemas
+                    .iter()
+                    .map(Self::from_value)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(object) => {
+                let mut fields = BTreeMap::new();
+                for (name, schema) in object {
+                    let (name, optional) = match name.strip_suffix('?') {
+                        Some(name) => (name, true),
+                        None => (name.as_str(), false),
+                    };
+                    fields.insert(
+                        name.to_string(),
+                        SchemaField {
+                            schema: Self::from_value(schema)?,
+                            optional,
+                        },
+                    );
+                }
+                Self::Object { fields, rest: None }
+            }
+            value => return Err(format!("{value} doesn't describe a type")),
+        })
+    }
+
+    fn from_name(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.contains('|') {
+            return Ok(Self::Union(
+                name.split('|')
+                    .map(Self::from_name)
+                    .collect::<Result<_, _>>()?,
+            ));
+        }
+        if let Some(element) = name.strip_suffix("[]") {
+            return Ok(Self::Array(Box::new(Self::from_name(element)?)));
+        }
+
+        Ok(match name {
+            "any" | "unknown" => Self::Any,
+            "string" => Self::String,
+            "number" => Self::Number,
+            "boolean" => Self::Boolean,
+            "null" => Self::Null,
+            "undefined" => Self::Undefined,
+            "object" => Self::Object {
+                fields: BTreeMap::new(),
+                rest: Some(Box::new(Self::Any)),
+            },
+            name => return Err(format!("unknown type {name:?}")),
+        })
+    }
+
+    /// The type of the first parameter of `class`' constructor, `None` if it has none.
+    /// Types declared in `module` are looked up, anything else is taken as [`ParamSchema::Any`].
+    pub fn from_constructor(class: &ClassDecl, module: &Module) -> Option<Self> {
+        let constructor = class.class.body.iter().find_map(|member| match member {
+            ClassMember::Constructor(constructor) => Some(constructor),
+            _ => None,
+        })?;
+
+        let pat = match constructor.params.first()? {
+            ParamOrTsParamProp::Param(param) => &param.pat,
+            ParamOrTsParamProp::TsParamProp(prop) => match &prop.param {
+                TsParamPropParam::Ident(ident) => return Self::binding(ident, module),
+                TsParamPropParam::Assign(assign) => assign.left.as_ref(),
+            },
+        };
+
+        let type_ann = match pat {
+            Pat::Ident(ident) => return Self::binding(ident, module),
+            Pat::Assign(assign) => match assign.left.as_ref() {
+                Pat::Ident(ident) => return Self::binding(ident, module),
+                _ => return None,
+            },
+            Pat::Object(object) => object.type_ann.as_ref(),
+            Pat::Array(array) => array.type_ann.as_ref(),
+            _ => None,
+        }?;
+        Some(TypeScope::new(module).resolve(&type_ann.type_ann))
+    }
+
+    fn binding(ident: &BindingIdent, module: &Module) -> Option<Self> {
+        let type_ann = ident.type_ann.as_ref()?;
+        Some(TypeScope::new(module).resolve(&type_ann.type_ann))
+    }
+
+    /// Everything in `value` not matching this schema
+    pub fn validate(&self, value: &Value) -> Vec<SchemaViolation> {
+        let mut out = vec![];
+        self.validate_at("$".to_string(), value, &mut out);
+        out
+    }
+
+    fn validate_at(&self, path: String, value: &Value, out: &mut Vec<SchemaViolation>) {
+        let matches = match (self, value) {
+            (ParamSchema::Any, _)
+            | (ParamSchema::String, Value::String(_))
+            | (ParamSchema::Number, Value::Number(_))
+            | (ParamSchema::Boolean, Value::Bool(_))
+            | (ParamSchema::Null, Value::Null) => true,
+            (ParamSchema::Literal(literal), value) => literal == value,
+            (ParamSchema::Array(schema), Value::Array(values)) => {
+                for (idx, value) in values.iter().enumerate() {
+                    schema.validate_at(format!("{path}[{idx}]"), value, out);
+                }
+                true
+            }
+            (ParamSchema::Tuple(schemas), Value::Array(values))
+                if schemas.len() == values.len() =>
+            {
+                for (idx, (schema, value)) in schemas.iter().zip(values).enumerate() {
+                    schema.validate_at(format!("{path}[{idx}]"), value, out);
+                }
+                true
+            }
+            (ParamSchema::Object { fields, rest }, Value::Object(object)) => {
+                for (name, field) in fields {
+                    let field_path = format!("{path}.{name}");
+                    match object.get(name) {
+                        Some(value) => field.schema.validate_at(field_path, value, out),
+                        None if field.optional || field.schema.accepts_missing() => {}
+                        None => out.push(SchemaViolation::Missing {
+                            path: field_path,
+                            expected: field.schema.to_string(),
+                        }),
+                    }
+                }
+                for (name, value) in object.iter().filter(|(e, _)| !fields.contains_key(*e)) {
+                    let field_path = format!("{path}.{name}");
+                    match rest {
+                        Some(rest) => rest.validate_at(field_path, value, out),
+                        None => out.push(SchemaViolation::Unknown { path: field_path }),
+                    }
+                }
+                true
+            }
+            (ParamSchema::Union(schemas), value) => schemas
+                .iter()
+                .any(|schema| schema.validate(value).is_empty()),
+            _ => false,
+        };
+
+        if !matches {
+            out.push(SchemaViolation::Mismatch {
+                path,
+                expected: self.to_string(),
+                found: value.clone(),
+            });
+        }
+    }
+
+    fn accepts_missing(&self) -> bool {
+        match self {
+            ParamSchema::Any | ParamSchema::Undefined => true,
+            ParamSchema::Union(schemas) => schemas.iter().any(Self::accepts_missing),
+            _ => false,
+        }
+    }
+}
+
+/// The interfaces and type aliases declared in a module
+struct TypeScope {
+    types: HashMap<String, TsType>,
+}
+
+impl TypeScope {
+    fn new(module: &Module) -> Self {
+        struct Collector {
+            types: HashMap<String, TsType>,
+        }
+
+        impl Visit for Collector {
+            fn visit_ts_interface_decl(&mut self, node: &swc_ecma_ast::TsInterfaceDecl) {
+                let mut members = node.body.body.clone();
+                // Fields of extended interfaces aren't known, so allow any
+                if !node.extends.is_empty() {
+                    members.push(TsTypeElement::TsIndexSignature(
+                        swc_ecma_ast::TsIndexSignature {
+                            params: vec![],
+                            type_ann: None,
+                            readonly: false,
+                            is_static: false,
+                            span: node.span,
+                        },
+                    ));
+                }
+                self.types.insert(
+                    node.id.sym.to_string(),
+                    TsType::TsTypeLit(TsTypeLit {
+                        span: node.span,
+                        members,
+                    }),
+                );
+            }
+
+            fn visit_ts_type_alias_decl(&mut self, node: &swc_ecma_ast::TsTypeAliasDecl) {
+                self.types
+                    .insert(node.id.sym.to_string(), node.type_ann.as_ref().clone());
+            }
+        }
+
+        let mut collector = Collector {
+            types: HashMap::new(),
+        };
+        module.visit_with(&mut collector);
+        Self {
+            types: collector.types,
+        }
+    }
+
+    fn resolve(&self, ty: &TsType) -> ParamSchema {
+        self.resolve_in(ty, &mut HashSet::new())
+    }
+
+    /// `visiting` holds the named types being resolved, recursive types end in
+    /// [`ParamSchema::Any`]
+    fn resolve_in(&self, ty: &TsType, visiting: &mut HashSet<String>) -> ParamSchema {
+        match ty {
+            TsType::TsKeywordType(keyword) => match keyword.kind {
+                TsKeywordTypeKind::TsStringKeyword => ParamSchema::String,
+                TsKeywordTypeKind::TsNumberKeyword => ParamSchema::Number,
+                TsKeywordTypeKind::TsBooleanKeyword => ParamSchema::Boolean,
+                TsKeywordTypeKind::TsNullKeyword => ParamSchema::Null,
+                TsKeywordTypeKind::TsUndefinedKeyword | TsKeywordTypeKind::TsVoidKeyword => {
+                    ParamSchema::Undefined
+                }
+                TsKeywordTypeKind::TsObjectKeyword => ParamSchema::Object {
+                    fields: BTreeMap::new(),
+                    rest: Some(Box::new(ParamSchema::Any)),
+                },
+                _ => ParamSchema::Any,
+            },
+            TsType::TsLitType(lit) => match &lit.lit {
+                TsLit::Str(str) => ParamSchema::Literal(Value::String(str.value.to_string())),
+                TsLit::Bool(bool) => ParamSchema::Literal(Value::Bool(bool.value)),
+                TsLit::Number(num) => ParamSchema::Literal(crate::arguments::number(num.value)),
+                _ => ParamSchema::Any,
+            },
+            TsType::TsArrayType(array) => {
+                ParamSchema::Array(Box::new(self.resolve_in(&array.elem_type, visiting)))
+            }
+            TsType::TsTupleType(tuple) => ParamSchema::Tuple(
+                tuple
+                    .elem_types
+                    .iter()
+                    .map(|e| self.resolve_in(&e.ty, visiting))
+                    .collect(),
+            ),
+            TsType::TsTypeLit(lit) => self.object(&lit.members, visiting),
+            TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(union)) => {
+                ParamSchema::Union(
+                    union
+                        .types
+                        .iter()
+                        .map(|e| self.resolve_in(e, visiting))
+                        .collect(),
+                )
+            }
+            TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
+                intersection,
+            )) => {
+                // Only intersections of objects are understood, their fields are merged
+                let mut merged = BTreeMap::new();
+                let mut merged_rest = None;
+                for ty in &intersection.types {
+                    match self.resolve_in(ty, visiting) {
+                        ParamSchema::Object { fields, rest } => {
+                            merged.extend(fields);
+                            merged_rest = merged_rest.or(rest);
+                        }
+                        _ => return ParamSchema::Any,
+                    }
+                }
+                ParamSchema::Object {
+                    fields: merged,
+                    rest: merged_rest,
+                }
+            }
+            TsType::TsParenthesizedType(ty) => self.resolve_in(&ty.type_ann, visiting),
+            TsType::TsOptionalType(ty) => self.resolve_in(&ty.type_ann, visiting),
+            TsType::TsTypeOperator(op) if op.op == TsTypeOperatorOp::ReadOnly => {
+                self.resolve_in(&op.type_ann, visiting)
+            }
+            TsType::TsTypeRef(reference) => {
+                let TsEntityName::Ident(name) = &reference.type_name else {
+                    return ParamSchema::Any;
+                };
+                let name = name.sym.as_str();
+                let params = reference
+                    .type_params
+                    .as_ref()
+                    .map(|e| e.params.as_slice())
+                    .unwrap_or_default();
+
+                match (name, params) {
+                    ("Array" | "ReadonlyArray", [element]) => {
+                        ParamSchema::Array(Box::new(self.resolve_in(element, visiting)))
+                    }
+                    ("Record", [_, value]) => ParamSchema::Object {
+                        fields: BTreeMap::new(),
+                        rest: Some(Box::new(self.resolve_in(value, visiting))),
+                    },
+                    (name, []) if !visiting.contains(name) => match self.types.get(name) {
+                        Some(ty) => {
+                            visiting.insert(name.to_string());
+                            let schema = self.resolve_in(ty, visiting);
+                            visiting.remove(name);
+                            schema
+                        }
+                        None => ParamSchema::Any,
+                    },
+                    _ => ParamSchema::Any,
+                }
+            }
+            _ => ParamSchema::Any,
+        }
+    }
+
+    fn object(&self, members: &[TsTypeElement], visiting: &mut HashSet<String>) -> ParamSchema {
+        let mut fields = BTreeMap::new();
+        let mut rest = None;
+        for member in members {
+            match member {
+                TsTypeElement::TsPropertySignature(prop) => {
+                    let name = match prop.key.as_ref() {
+                        Expr::Ident(ident) => ident.sym.to_string(),
+                        Expr::Lit(Lit::Str(str)) => str.value.to_string(),
+                        _ => continue,
+                    };
+                    let schema = prop
+                        .type_ann
+                        .as_ref()
+                        .map_or(ParamSchema::Any, |e| self.resolve_in(&e.type_ann, visiting));
+                    fields.insert(
+                        name,
+                        SchemaField {
+                            schema,
+                            optional: prop.optional,
+                        },
+                    );
+                }
+                TsTypeElement::TsIndexSignature(index) => {
+                    rest = Some(Box::new(
+                        index
+                            .type_ann
+                            .as_ref()
+                            .map_or(ParamSchema::Any, |e| self.resolve_in(&e.type_ann, visiting)),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        ParamSchema::Object { fields, rest }
+    }
+}
+
+/// Instance data not matching its component's schema, by the identifier of the block or item
+/// holding it
+#[derive(Debug, Error)]
+#[error("{}", format_type_errors(.0))]
+pub struct TypeCheckError(pub BTreeMap<String, Vec<String>>);
+
+fn format_type_errors(errors: &BTreeMap<String, Vec<String>>) -> String {
+    let mut out = "Component data failed type checking:".to_string();
+    for (identifier, errors) in errors {
+        out.push_str(&format!("\n  {identifier}:"));
+        for error in errors {
+            out.push_str(&format!("\n    {error}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{ParamSchema, SchemaViolation};
+    use serde_json::json;
+
+    #[test]
+    fn explicit_schema() {
+        let schema = ParamSchema::from_value(&json!({
+            "radius": "number",
+            "color?": "string | null",
+            "tags": ["string"],
+            "offset": ["number", "number"]
+        }))
+        .unwrap();
+
+        assert!(schema
+            .validate(&json!({"radius": 2, "tags": [], "offset": [0, 1.5]}))
+            .is_empty());
+        assert_eq!(
+            schema.validate(&json!({"radius": "2", "color": 1, "tags": ["a", 3], "size": 1})),
+            vec![
+                SchemaViolation::Mismatch {
+                    path: "$.color".to_string(),
+                    expected: "string | null".to_string(),
+                    found: json!(1)
+                },
+                SchemaViolation::Missing {
+                    path: "$.offset".to_string(),
+                    expected: "[number, number]".to_string()
+                },
+                SchemaViolation::Mismatch {
+                    path: "$.radius".to_string(),
+                    expected: "number".to_string(),
+                    found: json!("2")
+                },
+                SchemaViolation::Mismatch {
+                    path: "$.tags[1]".to_string(),
+                    expected: "string".to_string(),
+                    found: json!(3)
+                },
+                SchemaViolation::Unknown {
+                    path: "$.size".to_string()
+                },
+            ]
+        );
+        assert!(ParamSchema::from_value(&json!({"a": "numbr"})).is_err());
+    }
+}
@@ -6,7 +6,7 @@ use swc_common::comments::SingleThreadedComments;
 use swc_common::input::StringInput;
 use swc_common::BytePos;
 use swc_ecma_ast::Module;
-use swc_ecma_parser::{Parser, Syntax};
+use swc_ecma_parser::{Parser, Syntax, TsSyntax};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -90,7 +90,7 @@ impl ASTStage {
         Ok(FunctionRipper::new(self.config, ast))
     }
 
-    fn emit_single_impl(
+    pub(crate) fn emit_single_impl(
         &self,
         source: &String,
     ) -> Result<Option<(Module, SingleThreadedComments)>, ASTError> {
@@ -102,7 +102,10 @@ impl ASTStage {
 
         let mut parser = Parser::new(
             if self.config.parsed_config.mode == ParserMode::TS {
-                Syntax::Typescript(Default::default())
+                Syntax::Typescript(TsSyntax {
+                    decorators: true,
+                    ..Default::default()
+                })
             } else {
                 Syntax::Es(Default::default())
             },
@@ -1,23 +1,40 @@
+use crate::arguments::{number, parse_arguments, Argument, ArgumentError, Position};
 use crate::component_registry::{
     ComponentInformation, ComponentInformationError, ComponentStaticInformation,
     CustomComponentRegistry,
 };
 use crate::config::OfficeConfig;
 use crate::find_n_with_comments;
+use crate::schema::ParamSchema;
 use crate::stages::ast_stage::ASTImpl;
-use crate::string_extensions::*;
-use log::error;
+use log::{error, warn};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
 use swc_common::comments::Comment;
-use swc_ecma_ast::ClassDecl;
+use swc_common::{Span, Spanned};
+use swc_ecma_ast::{
+    CallExpr, Callee, ClassDecl, ClassExpr, Decorator, DefaultDecl, ExportDefaultDecl, Expr,
+    ExprOrSpread, KeyValueProp, Lit, ParenExpr, Prop, PropName, PropOrSpread, TsAsExpr,
+    TsConstAssertion, UnaryExpr, UnaryOp,
+};
+use swc_ecma_visit::{Visit, VisitWith};
 use thiserror::Error;
 
 const COMPONENT_START_IDENTIFIER: &'static str = "@Generate";
 const COMPONENT_START_IDENTIFIER_CONSUME: &'static str = "@Generate(";
+const COMPONENT_DECORATOR: &str = "Generate";
 
 #[derive(Debug, Error)]
 pub enum ComponentRipperError {
-    #[error("argument: {0}, doesn't follow the passing scheme of \"arg_id: arg_value\"")]
-    InvalidArgumentFormat(String),
+    #[error("Invalid @Generate arguments on {class}: {source}")]
+    InvalidArguments {
+        class: String,
+        source: ArgumentError,
+    },
+    #[error("the @Generate decorator of {0} must be passed a single object literal")]
+    InvalidDecorator(String),
+    #[error("argument of {class} at {position} can't be evaluated, only literals, arrays and objects are allowed")]
+    UnsupportedArgument { class: String, position: Position },
     #[error(transparent)]
     InformationParseError(#[from] ComponentInformationError),
 }
@@ -53,65 +70,33 @@ impl ComponentRipStage {
     }
 
     fn process_file(&self, ast: &ASTImpl) -> Option<Vec<ComponentInformation>> {
-        let classes = Self::pull_classes_with_comments(ast)?;
-
         let mut return_information = Vec::new();
+        let mut decorated = HashSet::new();
 
-        for (class, comments) in classes {
-            let comp_ci = {
-                let _class_info = class.class.as_ref();
-
-                let comment = Self::consolidate_comments(comments);
-
-                let parse_location = Self::contains_generate_expression(&comment)?;
-
-                let result = comment
-                    .slice_extended(ByteIndex(parse_location), EndIndex)
-                    .scoped_slice(&[
-                        ScopeDeclaration {
-                            begin: '{',
-                            end: '}',
-                        },
-                        ScopeDeclaration {
-                            begin: '[',
-                            end: ']',
-                        },
-                        ScopeDeclaration {
-                            begin: '(',
-                            end: ')',
-                        },
-                    ])?;
-
-                let args = match Self::build_argument_list(result) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        log::error!(
-                            "Failed to rip component class {}, with error {}",
-                            class.ident.sym.as_str(),
-                            e
-                        );
-                        continue;
-                    }
-                };
-
-                let info = match ComponentInformation::new(
-                    args,
-                    ComponentStaticInformation::builder()
-                        .class_id(class.ident.sym.as_str().to_string())
-                        .relative_path(ast.relative_path.clone())
-                        .build(),
-                ) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        error!("{}", e);
-                        continue;
-                    }
-                };
-                Some(info)
+        for (class, default_export) in Self::pull_classes(ast) {
+            let Some(args) = Self::decorator_arguments(&class, ast) else {
+                continue;
             };
+            decorated.insert(class.ident.sym.to_string());
 
-            if let Some(i) = comp_ci {
-                return_information.push(i);
+            match args.and_then(|args| Self::build_information(&class, default_export, ast, args)) {
+                Ok(info) => return_information.push(info),
+                Err(e) => error!("Failed to rip component: {}", e),
+            }
+        }
+
+        // Classes without a decorator may still declare themselves in a comment
+        for (class, comments) in Self::pull_classes_with_comments(ast).unwrap_or_default() {
+            if decorated.contains(class.ident.sym.as_str()) {
+                continue;
+            }
+
+            let Some(args) = Self::comment_arguments(&class, comments, ast) else {
+                continue;
+            };
+            match args.and_then(|args| Self::build_information(&class, false, ast, args)) {
+                Ok(info) => return_information.push(info),
+                Err(e) => error!("Failed to rip component: {}", e),
             }
         }
 
@@ -122,56 +107,190 @@ impl ComponentRipStage {
         }
     }
 
-    fn build_argument_list(whole_comment: &str) -> Result<Vec<(&str, &str)>, ComponentRipperError> {
-        let mut arg_count = Vec::new();
-        Self::pull_single_arg(whole_comment, &mut arg_count)?;
-        Ok(arg_count)
+    /// The arguments of a `@Generate(...)` in the comments before `class`, `None` if there is
+    /// none
+    fn comment_arguments(
+        class: &ClassDecl,
+        comments: Vec<Comment>,
+        ast: &ASTImpl,
+    ) -> Option<Result<Vec<Argument>, ComponentRipperError>> {
+        let (comment, segments) = Self::consolidate_comments(comments);
+        let start = Self::contains_generate_expression(&comment)?;
+
+        // Maps an offset in the arguments back to the comment it was written in
+        let locate = |offset: usize| {
+            let offset = start + offset;
+            let (segment, source) = segments
+                .iter()
+                .rev()
+                .find(|(segment, _)| *segment <= offset)
+                .copied()
+                .unwrap_or_default();
+            Position::of(&ast.source, source + offset - segment)
+        };
+
+        Some(
+            parse_arguments(&comment[start..], &locate).map_err(|source| {
+                ComponentRipperError::InvalidArguments {
+                    class: class.ident.sym.to_string(),
+                    source,
+                }
+            }),
+        )
     }
 
-    fn pull_single_arg<'a>(
-        comment_section: &'a str,
-        out: &mut Vec<(&'a str, &'a str)>,
-    ) -> Result<(), ComponentRipperError> {
-        let (current, next) = comment_section.scoped_split(
-            &',',
-            &[
-                ScopeDeclaration {
-                    begin: '{',
-                    end: '}',
-                },
-                ScopeDeclaration {
-                    begin: '[',
-                    end: ']',
-                },
-                ScopeDeclaration {
-                    begin: '(',
-                    end: ')',
+    fn build_information(
+        class: &ClassDecl,
+        default_export: bool,
+        ast: &ASTImpl,
+        args: Vec<Argument>,
+    ) -> Result<ComponentInformation, ComponentRipperError> {
+        let mut info = ComponentInformation::new(
+            args,
+            ComponentStaticInformation::builder()
+                .class_id(class.ident.sym.as_str().to_string())
+                .relative_path(ast.relative_path.clone())
+                .default_export(default_export)
+                .build(),
+        )?;
+        if info.schema.is_none() {
+            info.schema = ParamSchema::from_constructor(class, &ast.module);
+        }
+        Ok(info)
+    }
+
+    /// The `@Generate` decorator among `decorators`, with its call if it has one
+    fn generate_decorator(decorators: &[Decorator]) -> Option<Option<&CallExpr>> {
+        decorators.iter().find_map(|decorator| {
+            let is_generate = |expr: &Expr| {
+                matches!(expr, Expr::Ident(ident) if ident.sym.as_str() == COMPONENT_DECORATOR)
+            };
+            match decorator.expr.as_ref() {
+                Expr::Call(call) => match &call.callee {
+                    Callee::Expr(callee) if is_generate(callee) => Some(Some(call)),
+                    _ => None,
                 },
-            ],
-            false,
-            1,
-        );
+                expr if is_generate(expr) => Some(None),
+                _ => None,
+            }
+        })
+    }
 
-        let value = current
-            .split_once(':')
-            .ok_or(ComponentRipperError::InvalidArgumentFormat(
-                current.to_string(),
-            ))?;
-        out.push((value.0.trim(), value.1.trim()));
+    /// The arguments of a `@Generate({ ... })` decorator on `class`, `None` if there is none
+    fn decorator_arguments(
+        class: &ClassDecl,
+        ast: &ASTImpl,
+    ) -> Option<Result<Vec<Argument>, ComponentRipperError>> {
+        let expr = Self::generate_decorator(&class.class.decorators)?;
 
-        if let Some(val) = next {
-            Self::pull_single_arg(val.trim(), out)
-        } else {
-            Ok(())
+        let class_id = class.ident.sym.to_string();
+        let locate = |expr: &Expr| Position::of(&ast.source, expr.span().lo.0 as usize);
+        let object = match expr.map(|call| call.args.as_slice()) {
+            Some([ExprOrSpread { spread: None, expr }]) => match expr.as_ref() {
+                Expr::Object(object) => object,
+                _ => return Some(Err(ComponentRipperError::InvalidDecorator(class_id))),
+            },
+            _ => return Some(Err(ComponentRipperError::InvalidDecorator(class_id))),
+        };
+
+        let mut args = Vec::new();
+        for prop in &object.props {
+            let arg = Self::property(prop).and_then(|(name, value)| {
+                Ok(Argument {
+                    name,
+                    value: Self::evaluate(value)?,
+                    position: locate(value),
+                })
+            });
+            match arg {
+                Ok(arg) => args.push(arg),
+                Err(position) => {
+                    return Some(Err(ComponentRipperError::UnsupportedArgument {
+                        class: class_id,
+                        position: Position::of(&ast.source, position.lo.0 as usize),
+                    }))
+                }
+            }
         }
+        Some(Ok(args))
     }
 
-    // TODO: Make this use the source map and not do a useless clone
-    fn consolidate_comments(comments: Vec<Comment>) -> String {
-        comments
-            .into_iter()
-            .map(|ele| format!("{} ", ele.text.to_string()))
-            .collect()
+    /// The name and value of a `name: value` object property
+    fn property(prop: &PropOrSpread) -> Result<(String, &Expr), Span> {
+        let PropOrSpread::Prop(prop) = prop else {
+            return Err(prop.span());
+        };
+        let Prop::KeyValue(KeyValueProp { key, value }) = prop.as_ref() else {
+            return Err(prop.span());
+        };
+        let key = match key {
+            PropName::Ident(ident) => ident.sym.to_string(),
+            PropName::Str(str) => str.value.to_string(),
+            PropName::Num(num) => num.value.to_string(),
+            _ => return Err(key.span()),
+        };
+        Ok((key, value))
+    }
+
+    /// Evaluates a decorator argument, which may only be built out of literals. Fails with the
+    /// span of the part that isn't.
+    fn evaluate(expr: &Expr) -> Result<Value, Span> {
+        Ok(match expr {
+            Expr::Lit(Lit::Str(str)) => Value::String(str.value.to_string()),
+            Expr::Lit(Lit::Bool(bool)) => Value::Bool(bool.value),
+            Expr::Lit(Lit::Null(_)) => Value::Null,
+            Expr::Lit(Lit::Num(num)) => number(num.value),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Minus,
+                arg,
+                ..
+            }) => match arg.as_ref() {
+                Expr::Lit(Lit::Num(num)) => number(-num.value),
+                _ => return Err(expr.span()),
+            },
+            Expr::Tpl(tpl) if tpl.exprs.is_empty() => Value::String(
+                tpl.quasis
+                    .iter()
+                    .filter_map(|e| e.cooked.as_ref().map(|e| e.to_string()))
+                    .collect(),
+            ),
+            Expr::Array(array) => Value::Array(
+                array
+                    .elems
+                    .iter()
+                    .map(|e| match e {
+                        Some(ExprOrSpread { spread: None, expr }) => Self::evaluate(expr),
+                        _ => Err(array.span()),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Expr::Object(object) => {
+                let mut out = Map::new();
+                for prop in &object.props {
+                    let (key, value) = Self::property(prop)?;
+                    out.insert(key, Self::evaluate(value)?);
+                }
+                Value::Object(out)
+            }
+            Expr::Paren(ParenExpr { expr, .. })
+            | Expr::TsAs(TsAsExpr { expr, .. })
+            | Expr::TsConstAssertion(TsConstAssertion { expr, .. }) => Self::evaluate(expr)?,
+            _ => return Err(expr.span()),
+        })
+    }
+
+    /// Joins comments with a space, along with where each comment's text starts in the joined
+    /// string and in the source
+    fn consolidate_comments(comments: Vec<Comment>) -> (String, Vec<(usize, usize)>) {
+        let mut out = String::new();
+        let mut segments = Vec::new();
+        for comment in comments {
+            // The text starts after the `//` or `/*`
+            segments.push((out.len(), comment.span.lo.0 as usize + 2));
+            out.push_str(&comment.text);
+            out.push(' ');
+        }
+        (out, segments)
     }
 
     fn contains_generate_expression(str: &str) -> Option<usize> {
@@ -179,7 +298,241 @@ impl ComponentRipStage {
             .and_then(|ele| Option::from(ele + COMPONENT_START_IDENTIFIER.as_bytes().len()))
     }
 
+    /// Every class declaration, with whether it's the file's default export. Decorated class
+    /// expressions can't be imported by name, they're skipped with a warning.
+    fn pull_classes(ast: &ASTImpl) -> Vec<(ClassDecl, bool)> {
+        struct Collector<'a> {
+            ast: &'a ASTImpl,
+            out: Vec<(ClassDecl, bool)>,
+        }
+
+        impl Visit for Collector<'_> {
+            fn visit_class_decl(&mut self, node: &ClassDecl) {
+                self.out.push((node.clone(), false));
+                node.visit_children_with(self)
+            }
+
+            fn visit_export_default_decl(&mut self, node: &ExportDefaultDecl) {
+                let DefaultDecl::Class(ClassExpr {
+                    ident: Some(ident),
+                    class,
+                }) = &node.decl
+                else {
+                    return node.visit_children_with(self);
+                };
+
+                let decl = ClassDecl {
+                    ident: ident.clone(),
+                    declare: false,
+                    class: class.clone(),
+                };
+                self.out.push((decl, true));
+                class.visit_with(self)
+            }
+
+            fn visit_class_expr(&mut self, node: &ClassExpr) {
+                if ComponentRipStage::generate_decorator(&node.class.decorators).is_some() {
+                    warn!(
+                        "Skipped @Generate on an unnamed class at {} in {}",
+                        Position::of(&self.ast.source, node.class.span.lo.0 as usize),
+                        self.ast.relative_path.display()
+                    );
+                }
+                node.visit_children_with(self)
+            }
+        }
+
+        let mut collector = Collector {
+            ast,
+            out: Vec::new(),
+        };
+        ast.module.visit_with(&mut collector);
+        collector.out
+    }
+
     fn pull_classes_with_comments(ast: &ASTImpl) -> Option<Vec<(ClassDecl, Vec<Comment>)>> {
         find_n_with_comments!(class, ast)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::component_registry::{ComponentType, CustomComponentRegistry};
+    use crate::config::{ExposedOfficeConfig, OfficeConfig};
+    use crate::stages::ast_stage::{ASTImpl, ASTStage};
+    use crate::stages::component_rip_stage::ComponentRipStage;
+    use crate::stages::json_application_stage::register_component_types;
+    use rusted_shut::test_utils::{addon, block};
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::pin::Pin;
+
+    fn parse(source: &str) -> (OfficeConfig, ASTImpl) {
+        let config = OfficeConfig {
+            parsed_config: ExposedOfficeConfig::default(),
+            script_entry: "main".to_string(),
+            script_search_location: Box::from(Path::new("./")),
+        };
+        let source = Pin::new(Box::new(source.to_string()));
+        let (module, comments) = ASTStage {
+            config: config.clone(),
+        }
+        .emit_single_impl(&source)
+        .unwrap()
+        .unwrap();
+
+        let ast = ASTImpl {
+            source,
+            module,
+            comments,
+            relative_path: Box::from(Path::new("components.ts")),
+        };
+        (config, ast)
+    }
+
+    #[test]
+    fn rips_decorators_and_comments() {
+        let (config, ast) = parse(
+            r#"
+            @Generate({ id: "azur:glow", type: `block`, pureData: true })
+            export class Glow {}
+
+            // @Generate(id: azur:spin, type: item)
+            class Spin {}
+
+            @Generate({ id: "azur:broken", type: "block", data: someValue })
+            class Broken {}
+
+            class Plain {}
+
+            @Generate({ id: "azur:spark", type: "item" })
+            export default class Spark {}
+
+            // Can't be imported by name
+            const Lost = @Generate({ id: "azur:lost", type: "item" }) class {};
+            "#,
+        );
+        let found = ComponentRipStage::new(config, vec![])
+            .process_file(&ast)
+            .unwrap();
+
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].search_id, "azur:glow");
+        assert_eq!(found[0].component_type, ComponentType::Block);
+        assert!(found[0].is_pure_data);
+        assert_eq!(found[0].information.class_id, "Glow");
+        assert!(!found[0].information.default_export);
+        assert_eq!(found[1].search_id, "azur:spark");
+        assert!(found[1].information.default_export);
+        assert_eq!(found[2].search_id, "azur:spin");
+        assert_eq!(found[2].component_type, ComponentType::Item);
+    }
+
+    #[test]
+    fn reports_bad_arguments() {
+        let (_, ast) = parse(concat!(
+            "// ünï\n",
+            "// @Generate(id: \"azur:a, b\",\n",
+            "//   type: item, passId: yes)\n",
+            "class Spin {}\n",
+            "/* @Generate(id: \"azur:é\\q\") */\n",
+            "class Broken {}\n",
+            "@Generate({ id: \"azur:é\", type: \"block\", data: [1, someValue] })\n",
+            "class Glow {}\n",
+        ));
+
+        let mut errors = vec![];
+        for (class, comments) in ComponentRipStage::pull_classes_with_comments(&ast).unwrap() {
+            let args = ComponentRipStage::comment_arguments(&class, comments, &ast).unwrap();
+            let error = args
+                .and_then(|args| ComponentRipStage::build_information(&class, false, &ast, args))
+                .unwrap_err();
+            errors.push(error.to_string());
+        }
+        let (glow, _) = ComponentRipStage::pull_classes(&ast).remove(2);
+        errors.push(
+            ComponentRipStage::decorator_arguments(&glow, &ast)
+                .unwrap()
+                .unwrap_err()
+                .to_string(),
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "Invalid value for param passId of Spin at 3:26: expected a boolean, found \"yes\"",
+                "Invalid @Generate arguments on Broken: invalid escape \\q at 5:25",
+                "argument of Glow at 7:52 can't be evaluated, only literals, arrays and objects are allowed",
+            ]
+        );
+    }
+
+    #[test]
+    fn type_checks_instances() {
+        let (config, ast) = parse(
+            r#"
+            interface GlowData {
+                level: number;
+                color?: "red" | "blue";
+            }
+
+            @Generate({ id: "azur:glow", type: "block" })
+            export class Glow {
+                constructor(data: GlowData) {}
+            }
+
+            @Generate({ id: "azur:spin", type: "block", schema: { speed: "number" } })
+            export class Spin {
+                constructor(data: any) {}
+            }
+            "#,
+        );
+        let found = ComponentRipStage::new(config, vec![])
+            .process_file(&ast)
+            .unwrap();
+        assert_eq!(
+            found[0].schema.as_ref().unwrap().to_string(),
+            "{ color?: \"red\" | \"blue\", level: number }"
+        );
+        assert_eq!(
+            found[1].schema.as_ref().unwrap().to_string(),
+            "{ speed: number }"
+        );
+
+        let addon = addon([
+            block(
+                "azur:lamp",
+                json!({"azur:glow": {"level": "3", "color": "green"}, "azur:spin": {"speed": 1}}),
+            ),
+            block("azur:fan", json!({"azur:spin": {"sped": 1}})),
+            block("azur:torch", json!({"azur:glow": {"level": 3}})),
+        ]);
+
+        let mut processor =
+            register_component_types(CustomComponentRegistry::build_from_list(found));
+        processor.state_mut().type_check = true;
+        processor.process_addon(addon).unwrap();
+
+        assert_eq!(
+            processor.state().type_errors,
+            BTreeMap::from([
+                (
+                    "azur:fan".to_string(),
+                    vec![
+                        "azur:spin: $.speed: missing, expected number".to_string(),
+                        "azur:spin: $.sped: unknown field".to_string(),
+                    ]
+                ),
+                (
+                    "azur:lamp".to_string(),
+                    vec![
+                        "azur:glow: $.color: expected \"red\" | \"blue\", found \"green\""
+                            .to_string(),
+                        "azur:glow: $.level: expected number, found \"3\"".to_string(),
+                    ]
+                ),
+            ])
+        );
+    }
+}
@@ -114,24 +114,22 @@ impl CodeEmitter {
             str
         };
 
+        let class = |ci: &ComponentInformation| {
+            if ci.information.default_export {
+                ci.information.class_id.clone()
+            } else {
+                format!("{{ {} }}", ci.information.class_id)
+            }
+        };
+
         for ci in self.registry.block_list_iter() {
-            writeln!(
-                out,
-                "import {{ {} }} from \"./{}\";",
-                ci.information.class_id,
-                path(ci)
-            )
-            .expect("Failed to write");
+            writeln!(out, "import {} from \"./{}\";", class(ci), path(ci))
+                .expect("Failed to write");
         }
 
         for ii in self.registry.item_list_iter() {
-            writeln!(
-                out,
-                "import {{ {} }} from \"./{}\";",
-                ii.information.class_id,
-                path(ii)
-            )
-            .expect("Failed to write");
+            writeln!(out, "import {} from \"./{}\";", class(ii), path(ii))
+                .expect("Failed to write");
         }
 
         for fi in &self.functions {
@@ -1,9 +1,9 @@
-use crate::component_registry::CustomComponentRegistry;
-use rusted_shut::addon::addon::Addon;
+use crate::component_registry::{ComponentInformation, CustomComponentRegistry};
 use rusted_shut::addon::blocks::block::Block;
 use rusted_shut::addon::component_store::ComponentStore;
 use rusted_shut::addon::components::custom_components::MinecraftCustomComponents;
 use rusted_shut::addon::custom_infrastructure::addon_processor::AddonProcessor;
+use rusted_shut::addon::custom_infrastructure::addon_context::AddonContext;
 use rusted_shut::addon::custom_infrastructure::component::custom_base::CustomComponent;
 use rusted_shut::addon::custom_infrastructure::component::custom_block::{
     CustomBlockComponent, GenericBlockCustomComponent,
@@ -14,7 +14,9 @@ use rusted_shut::addon::custom_infrastructure::component::custom_item::{
 use rusted_shut::addon::items::item::Item;
 use serde_json::Value;
 use std::any::Any;
+use std::collections::BTreeMap;
 use std::hint::unreachable_unchecked;
+use std::rc::Rc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -24,11 +26,40 @@ pub enum ItemError {}
 
 pub struct OfficeState {
     pub registry: CustomComponentRegistry,
+    /// Validates instance data against the component's schema, see [`crate::schema::ParamSchema`]
+    pub type_check: bool,
+    /// Schema violations by the identifier of the block or item holding the data
+    pub type_errors: BTreeMap<String, Vec<String>>,
 }
 
 impl OfficeState {
     pub fn new(registry: CustomComponentRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            type_check: false,
+            type_errors: BTreeMap::new(),
+        }
+    }
+
+    fn check(&mut self, info: Option<Rc<ComponentInformation>>, owner_id: &str, data: &Value) {
+        let Some(schema) = info.as_ref().and_then(|e| e.schema.as_ref()) else {
+            return;
+        };
+        if !self.type_check {
+            return;
+        }
+
+        let errors = schema
+            .validate(data)
+            .into_iter()
+            .map(|e| format!("{}: {}", info.as_ref().unwrap().search_id, e))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            self.type_errors
+                .entry(owner_id.to_string())
+                .or_default()
+                .extend(errors);
+        }
     }
 }
 
@@ -80,9 +111,12 @@ impl CustomBlockComponent for CustomComponentInstance {
         data: &serde_json::value::Value,
         owner: &mut Block,
         component_context: &mut ComponentStore,
-        _: Option<&mut Addon>,
+        _: &mut AddonContext,
         state: &mut Self::UserState,
     ) -> Result<(), Self::Error> {
+        let info = state.registry.block_information(self.id()).cloned();
+        state.check(info, &owner.description.identifier, data);
+
         let (r, skip) = state.registry.instance_component_block(
             self.id(),
             &owner.description.identifier,
@@ -132,9 +166,12 @@ impl CustomItemComponent for CustomComponentInstance {
         data: &Value,
         owner: &mut Item,
         component_context: &mut ComponentStore,
-        _: Option<&mut Addon>,
+        _: &mut AddonContext,
         state: &mut Self::UserState,
     ) -> Result<(), Self::Error> {
+        let info = state.registry.item_information(self.id()).cloned();
+        state.check(info, &owner.description.identifier, data);
+
         let (r, skip)  = state.registry.instance_component_item(
             self.id(),
             &owner.description.identifier,
@@ -59,12 +59,14 @@ impl<'a> StringCursor<'a> {
     }
 
     pub fn next(&mut self) -> Option<&str> {
-        self.index += 1;
-        self.underlying.get(self.index - 1..self.index)
+        let start = self.index;
+        self.index += self.remaining().chars().next()?.len_utf8();
+        Some(&self.underlying[start..self.index])
     }
 
     pub fn peak(&self) -> Option<&str> {
-        self.underlying.get(self.index..self.index + 1)
+        let char = self.remaining().chars().next()?;
+        Some(&self.underlying[self.index..self.index + char.len_utf8()])
     }
 
     pub fn next_if(&mut self, condition: impl StringCursorPredicate) -> bool {
@@ -119,9 +121,10 @@ pub trait IndexConvert: Clone + Copy {
     fn to_str_index(self, str: &str) -> usize;
 }
 
+/// Counts characters, not bytes. An index past the end maps to the end.
 impl IndexConvert for CharIndex {
-    fn to_str_index(self, _: &str) -> usize {
-        self.0
+    fn to_str_index(self, str: &str) -> usize {
+        str.char_indices().nth(self.0).map_or(str.len(), |(i, _)| i)
     }
 }
 impl IndexConvert for EndIndex {
@@ -130,21 +133,14 @@ impl IndexConvert for EndIndex {
     }
 }
 
+/// Moves back to the start of the character the byte is in, so slicing never panics
 impl IndexConvert for ByteIndex {
-    fn to_str_index(self, s: &str) -> usize {
-        let mut char_index = 0;
-        let mut byte_pos = 0;
-        let byte_index = self.0;
-
-        for (i, c) in s.char_indices() {
-            if byte_pos == byte_index {
-                return char_index;
-            }
-            byte_pos = i + c.len_utf8(); // advance to the next character's byte position
-            char_index += 1;
+    fn to_str_index(self, str: &str) -> usize {
+        let mut index = self.0.min(str.len());
+        while !str.is_char_boundary(index) {
+            index -= 1;
         }
-
-        panic!("Invalid byte index")
+        index
     }
 }
 
@@ -208,7 +204,7 @@ impl StringExtension for str {
                     Some(&self[idx + haystack_size * !include_haystack as usize..]),
                 );
             } else {
-                idx += 1;
+                idx += self[idx..].chars().next().map_or(1, char::len_utf8);
             }
         }
 
@@ -222,7 +218,9 @@ impl StringExtension for str {
 
 #[cfg(test)]
 mod tests {
-    use crate::string_extensions::{ScopeDeclaration, StringExtension};
+    use crate::string_extensions::{
+        ByteIndex, CharIndex, EndIndex, ScopeDeclaration, StringExtension,
+    };
 
     #[test]
     fn scoped_slice() {
@@ -285,4 +283,32 @@ mod tests {
             assert_eq!(expected, out);
         }
     }
+
+    #[test]
+    fn multibyte_text() {
+        let str = "ünï (çödé, [é]) ok";
+        assert_eq!(str.slice_extended(CharIndex(4), EndIndex), "(çödé, [é]) ok");
+        // The middle of `ü` moves back to its start
+        assert_eq!(str.slice_extended(ByteIndex(1), CharIndex(3)), "ünï");
+
+        let scope = [
+            ScopeDeclaration {
+                begin: '(',
+                end: ')',
+            },
+            ScopeDeclaration {
+                begin: '[',
+                end: ']',
+            },
+        ];
+        let inner = str
+            .slice_extended(CharIndex(4), EndIndex)
+            .scoped_slice(&scope)
+            .unwrap();
+        assert_eq!(inner, "çödé, [é]");
+        assert_eq!(
+            inner.scoped_split(&',', &scope, false, 1),
+            ("çödé", Some(" [é]"))
+        );
+    }
 }
@@ -0,0 +1,59 @@
+use crate::placer::RotationPlacer;
+use crate::{Rotation, RotationState};
+use rusted_shut::addon::addon::Addon;
+use rusted_shut::addon::custom_infrastructure::addon_processor::{AddonProcessor, ProcessingError};
+use rusted_shut::addon::custom_infrastructure::component::typed::{ComponentConfigError, Typed};
+use rusted_shut::addon::custom_infrastructure::processing_report::ProcessingReport;
+use rusted_shut::pipeline::filter::{Filter, FilterContext};
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotationFilterConfig {
+    /// Lets items placing a rotated block replace the block's item, see [`RotationPlacer`]
+    #[serde(default)]
+    pub placers: bool,
+}
+
+/// Applies `azur:rotation` to every block carrying it
+pub struct RotationFilter {
+    pub config: RotationFilterConfig,
+    report: Option<ProcessingReport>,
+}
+
+impl Filter for RotationFilter {
+    type Config = RotationFilterConfig;
+    type Error = ProcessingError<ComponentConfigError, ComponentConfigError>;
+
+    fn name() -> &'static str {
+        "rotation"
+    }
+
+    fn new(config: Self::Config, _: &FilterContext) -> Self {
+        Self {
+            config,
+            report: None,
+        }
+    }
+
+    fn run(&mut self, addon: Addon) -> Result<Addon, Self::Error> {
+        let mut processor =
+            AddonProcessor::<ComponentConfigError, ComponentConfigError, RotationState>::new(
+                RotationState {
+                    placers: self.config.placers,
+                },
+            );
+
+        processor.bind_block_component(Typed(Rotation));
+        if self.config.placers {
+            processor.bind_item_component(Typed(RotationPlacer));
+        }
+
+        let addon = processor.process_addon(addon)?;
+        self.report = Some(processor.take_report());
+        Ok(addon)
+    }
+
+    fn report(&self) -> Option<ProcessingReport> {
+        self.report.clone()
+    }
+}
@@ -1,33 +1,92 @@
 #![allow(deprecated)]
-use rusted_shut::addon::addon::Addon;
-use rusted_shut::addon::blocks::block::{Block, PlacementDirection, Trait};
+use crate::placer::{RotationPlacer, RotationPlacerConfig};
+use crate::transform::{compose, rotate_box, Transformation};
+use rusted_shut::addon::blocks::block::{Block, PlacementDirection, PlacementPosition, Trait};
 use rusted_shut::addon::blocks::permutation::Permutation;
-use rusted_shut::addon::component::{Component, UnknownComponent};
+use rusted_shut::addon::component::UnknownComponent;
 use rusted_shut::addon::component_store::ComponentStore;
+use rusted_shut::addon::custom_infrastructure::addon_context::AddonContext;
 use rusted_shut::addon::custom_infrastructure::component::custom_base::CustomComponent;
-use rusted_shut::addon::custom_infrastructure::component::custom_block::{
-    CustomBlockComponent, EmptyBlockState,
+use rusted_shut::addon::custom_infrastructure::component::typed::{
+    ComponentConfigError, TypedBlockComponent,
 };
 use serde::Deserialize;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::any::Any;
 use std::collections::HashMap;
 use strum_macros::{Display, EnumString, ToString};
 
+pub mod filter;
+pub mod placer;
+pub mod transform;
+
+#[derive(Debug, Default)]
+pub struct RotationState {
+    /// Adds [`placer::RotationPlacer`] to items placing a rotated block, for when it's bound
+    pub placers: bool,
+}
+
+/// Applies `azur:rotation`
+#[derive(Clone)]
 pub struct Rotation;
 #[derive(Deserialize)]
-struct RotationConfig {
+pub struct RotationConfig {
+    /// Shorthand for the facing mode, used if no `mode` is given
     #[serde(default)]
     y_rotation: bool,
+    #[serde(default)]
+    mode: Option<Mode>,
+    /// Turns the block upside down when placed in the upper half, for slabs and stairs
+    #[serde(default)]
+    vertical_half: bool,
+    /// `y_rotation_offset` of the placement direction trait, 180 for new traits if not given
+    #[serde(default)]
+    y_rotation_offset: Option<i32>,
+    /// Rotation of the model in its file, if it doesn't face north (or along y for axis)
+    #[serde(default)]
+    offset: [f64; 3],
+    /// Rotations replacing the built in ones, by direction. The axis mode uses `x`, `y` and `z`.
+    #[serde(default)]
+    directions: HashMap<String, [f64; 3]>,
+    #[serde(default)]
+    target: Target,
+    /// Icon texture for items placing the block, see [`placer::RotationPlacer`]
+    #[serde(default)]
+    icon: Option<String>,
+}
+
+impl RotationConfig {
+    fn mode(&self) -> Mode {
+        match self.mode {
+            Some(mode) => mode,
+            None if self.y_rotation => Mode::Facing,
+            None => Mode::Cardinal,
+        }
+    }
 }
 
-#[derive(ToString, Eq, PartialEq)]
+#[derive(ToString, Deserialize, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
 enum Mode {
     #[strum(serialize = "minecraft:cardinal_direction")]
     Cardinal,
     #[strum(serialize = "minecraft:facing_direction")]
     Facing,
+    #[strum(serialize = "minecraft:block_face")]
+    Axis,
 }
+
+#[derive(Deserialize, Default, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Target {
+    #[default]
+    Transformation,
+    /// A bone per direction, each shown by its own `minecraft:geometry`
+    Bones,
+    /// A bone per direction, shown by molang on the block's own `minecraft:geometry`
+    State,
+}
+
 impl CustomComponent for Rotation {
     fn as_any(&self) -> &dyn Any {
         self
@@ -95,145 +154,608 @@ const UP_DOWN: [TranslationInformation; 2] = [
     },
 ];
 
+const PLACEMENT_POSITION: &str = "minecraft:placement_position";
+
+const BOTTOM_HALF: &str = "q.block_state('minecraft:vertical_half') == 'bottom'";
+const TOP_HALF: &str = "q.block_state('minecraft:vertical_half') == 'top'";
+
+const UPSIDE_DOWN: [f64; 3] = [180.0, 0.0, 0.0];
+
+const ROTATED: [&str; 4] = [
+    "minecraft:transformation",
+    "minecraft:geometry",
+    "minecraft:collision_box",
+    "minecraft:selection_box",
+];
+
+const BOXES: [&str; 2] = ["minecraft:collision_box", "minecraft:selection_box"];
+
+const AXES: [(&str, &[Dir], [i32; 3]); 3] = [
+    ("y", &[Dir::up, Dir::down], [0, 0, 0]),
+    ("z", &[Dir::north, Dir::south], [90, 0, 0]),
+    ("x", &[Dir::east, Dir::west], [0, 0, 90]),
+];
+
+struct Orientation {
+    name: String,
+    condition: String,
+    rotation: [f64; 3],
+}
+
 impl Rotation {
-    fn bind_perms(blk: &mut Block, add_y: bool) {
-        let mode = if add_y { Mode::Facing } else { Mode::Cardinal };
-
-        for info in SHARED {
-            blk.permutations.push(Permutation::new(
-                format!("q.block_state('{}') == '{}'", mode.to_string(), info.dir),
-                ComponentStore::from_map(HashMap::from([(
-                    "minecraft:transformation".to_string(),
-                    Box::new(UnknownComponent::new(
-                        json!({"rotation": info.transform}),
-                        "minecraft:transformation".to_string(),
-                    )) as Box<dyn Component>,
-                )])),
-            ));
+    fn orientations(config: &RotationConfig) -> Vec<Orientation> {
+        let mode = config.mode();
+        let state = mode.to_string();
+        let condition = |dir: &Dir| format!("q.block_state('{state}') == '{dir}'");
+
+        let orientations = match mode {
+            Mode::Cardinal => SHARED
+                .iter()
+                .map(|info| (info.dir.to_string(), condition(&info.dir), info.transform))
+                .collect::<Vec<_>>(),
+            Mode::Facing => SHARED
+                .iter()
+                .chain(UP_DOWN.iter())
+                .map(|info| (info.dir.to_string(), condition(&info.dir), info.transform))
+                .collect(),
+            Mode::Axis => AXES
+                .iter()
+                .map(|(axis, dirs, transform)| {
+                    let condition = dirs.iter().map(condition).collect::<Vec<_>>().join(" || ");
+                    (axis.to_string(), condition, *transform)
+                })
+                .collect(),
+        };
+        let orientations = orientations
+            .into_iter()
+            .map(|(name, condition, transform)| {
+                let rotation = config
+                    .directions
+                    .get(&name)
+                    .copied()
+                    .unwrap_or(transform.map(f64::from));
+                Orientation {
+                    rotation: compose(rotation, config.offset),
+                    name,
+                    condition,
+                }
+            });
+
+        if !config.vertical_half {
+            return orientations.collect();
         }
 
-        if mode == Mode::Cardinal {
-            return;
+        // The flip happens in the block's own space, before it's turned to face its direction
+        orientations
+            .flat_map(|e| {
+                [
+                    Orientation {
+                        name: e.name.clone(),
+                        condition: format!("({}) && ({BOTTOM_HALF})", e.condition),
+                        rotation: e.rotation,
+                    },
+                    Orientation {
+                        name: format!("{}_top", e.name),
+                        condition: format!("({}) && ({TOP_HALF})", e.condition),
+                        rotation: compose(e.rotation, UPSIDE_DOWN),
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    fn unknown<'a>(components: &'a ComponentStore, id: &str) -> Option<&'a UnknownComponent> {
+        components.get_component_ref::<UnknownComponent>(id)
+    }
+
+    fn set_unknown(components: &mut ComponentStore, id: &str, data: Value) {
+        components.set_component_box(
+            Box::new(UnknownComponent::new(data, id.to_string())),
+            id.to_string(),
+        );
+    }
+
+    fn with_bones(geometry: &Value, bones: impl Iterator<Item = (String, Value)>) -> Value {
+        let mut geometry = match geometry {
+            Value::String(identifier) => json!({ "identifier": identifier }),
+            geometry => geometry.clone(),
+        };
+        if let Some(geometry) = geometry.as_object_mut() {
+            let visibility = geometry
+                .entry("bone_visibility")
+                .or_insert_with(|| json!({}));
+            if let Some(visibility) = visibility.as_object_mut() {
+                visibility.extend(bones);
+            }
+        }
+        geometry
+    }
+
+    fn rotate_components(
+        components: &mut ComponentStore,
+        base: &ComponentStore,
+        orientation: &Orientation,
+        all: &[Orientation],
+        target: Target,
+    ) {
+        let inherited = |components: &ComponentStore, id: &str| {
+            Self::unknown(components, id)
+                .or_else(|| Self::unknown(base, id))
+                .map(|e| e.data.clone())
+        };
+
+        match target {
+            Target::Bones => {
+                if let Some(geometry) = inherited(components, "minecraft:geometry") {
+                    let bones = all
+                        .iter()
+                        .map(|e| (e.name.clone(), json!(e.name == orientation.name)));
+                    Self::set_unknown(
+                        components,
+                        "minecraft:geometry",
+                        Self::with_bones(&geometry, bones),
+                    );
+                }
+            }
+            Target::Transformation => {
+                let transformation = inherited(components, "minecraft:transformation")
+                    .and_then(|e| Transformation::from_json(&e))
+                    .unwrap_or_default();
+                Self::set_unknown(
+                    components,
+                    "minecraft:transformation",
+                    transformation.rotated(orientation.rotation).to_json(),
+                );
+            }
+            // The geometry shows every direction through molang, see `bind_perms`
+            Target::State => {}
+        }
+
+        for id in BOXES {
+            let rotated =
+                inherited(components, id).and_then(|e| rotate_box(&e, orientation.rotation));
+            if let Some(rotated) = rotated {
+                Self::set_unknown(components, id, rotated);
+            }
         }
+    }
+
+    fn bind_perms(blk: &mut Block, components: &mut ComponentStore, config: &RotationConfig) {
+        let orientations = Self::orientations(config);
 
-        for info in UP_DOWN {
-            blk.permutations.push(Permutation::new(
-                format!("q.block_state('{}') == '{}'", mode.to_string(), info.dir),
-                ComponentStore::from_map(HashMap::from([(
-                    "minecraft:transformation".to_string(),
+        // Only the boxes are left to rotate per direction
+        let crossed: &[&str] = if config.target == Target::State {
+            let state_bones = |components: &mut ComponentStore| {
+                if let Some(geometry) = Self::unknown(components, "minecraft:geometry") {
+                    let bones = orientations
+                        .iter()
+                        .map(|e| (e.name.clone(), json!(e.condition)));
+                    let geometry = Self::with_bones(&geometry.data, bones);
+                    Self::set_unknown(components, "minecraft:geometry", geometry);
+                }
+            };
+            state_bones(components);
+            for perm in &mut blk.permutations {
+                state_bones(&mut perm.components);
+            }
+            &BOXES
+        } else {
+            &ROTATED
+        };
+
+        let mut permutations = orientations
+            .iter()
+            .map(|orientation| {
+                let mut rotated = ComponentStore::new();
+                Self::rotate_components(
+                    &mut rotated,
+                    components,
+                    orientation,
+                    &orientations,
+                    config.target,
+                );
+                Permutation::new(orientation.condition.clone(), rotated)
+            })
+            .filter(|e| e.components.ids().next().is_some())
+            .collect::<Vec<_>>();
+
+        for perm in std::mem::take(&mut blk.permutations) {
+            if !perm
+                .components
+                .ids()
+                .any(|id| crossed.contains(&id.as_str()))
+            {
+                permutations.push(perm);
+                continue;
+            }
+
+            for orientation in &orientations {
+                let mut rotated = perm.components.clone();
+                Self::rotate_components(
+                    &mut rotated,
+                    components,
+                    orientation,
+                    &orientations,
+                    config.target,
+                );
+                permutations.push(Permutation::new(
+                    format!("({}) && ({})", perm.condition, orientation.condition),
+                    rotated,
+                ));
+            }
+        }
+
+        blk.permutations = permutations;
+    }
+
+    fn enable_state<'a>(
+        blk: &'a mut Block,
+        name: &str,
+        default: Trait,
+        state: String,
+    ) -> &'a mut Trait {
+        let placement = blk
+            .description
+            .traits
+            .entry(name.to_string())
+            .or_insert(default);
+        let enabled_states = match placement {
+            Trait::PlacementPosition(position) => &mut position.enabled_states,
+            Trait::PlacementDirection(direction) => &mut direction.enabled_states,
+        };
+        if !enabled_states.contains(&state) {
+            enabled_states.push(state);
+        }
+        placement
+    }
+
+    fn bind_placers(blk: &Block, config: &RotationConfig, addon: &mut AddonContext) {
+        let id = &blk.description.identifier;
+        let items = addon
+            .addon()
+            .items_ref()
+            .iter()
+            .filter(|(item, _)| !addon.addon().is_item_read_only(item))
+            .filter(|(_, item)| item.placed_block() == Some(id))
+            .map(|(item, _)| item.clone())
+            .collect::<Vec<_>>();
+
+        for item in items {
+            let placer = RotationPlacerConfig {
+                block: id.clone(),
+                icon: config.icon.clone(),
+            };
+            addon.edit_item(&item, move |item| {
+                item.components.set_component_box(
                     Box::new(UnknownComponent::new(
-                        json!({"rotation": info.transform}),
-                        "minecraft:transformation".to_string(),
-                    )) as Box<dyn Component>,
-                )])),
-            ));
+                        json!(placer),
+                        RotationPlacer::static_id().to_string(),
+                    )),
+                    RotationPlacer::static_id().to_string(),
+                );
+            });
         }
     }
-}
 
-impl CustomBlockComponent for Rotation {
-    type UserState = EmptyBlockState;
-    type Error = serde_json::Error;
-    fn block_clone(
-        &self,
-    ) -> Box<dyn CustomBlockComponent<Error = Self::Error, UserState = Self::UserState>> {
-        Box::new(Self)
+    fn bind_trait(blk: &mut Block, config: &RotationConfig) {
+        let position = || Trait::PlacementPosition(PlacementPosition::default());
+        let mode = config.mode();
+
+        match mode {
+            Mode::Axis => {
+                Self::enable_state(blk, PLACEMENT_POSITION, position(), mode.to_string());
+            }
+            Mode::Cardinal | Mode::Facing => {
+                let direction = PlacementDirection {
+                    y_rotation_offset: config.y_rotation_offset.unwrap_or(180),
+                    ..Default::default()
+                };
+                let placement = Self::enable_state(
+                    blk,
+                    "minecraft:placement_direction",
+                    Trait::PlacementDirection(direction),
+                    mode.to_string(),
+                );
+                if let (Trait::PlacementDirection(direction), Some(offset)) =
+                    (placement, config.y_rotation_offset)
+                {
+                    direction.y_rotation_offset = offset;
+                }
+            }
+        }
+
+        if config.vertical_half {
+            Self::enable_state(
+                blk,
+                PLACEMENT_POSITION,
+                position(),
+                "minecraft:vertical_half".to_string(),
+            );
+        }
     }
+}
+
+impl TypedBlockComponent for Rotation {
+    type Config = RotationConfig;
+    type UserState = RotationState;
+    type Error = ComponentConfigError;
 
-    fn apply_component(
+    fn apply(
         &mut self,
-        data: &serde_json::value::Value,
+        config: Self::Config,
         owner: &mut Block,
-        _: &mut ComponentStore,
-        _: Option<&mut Addon>,
-        _: &mut Self::UserState,
+        component_context: &mut ComponentStore,
+        addon: &mut AddonContext,
+        state: &mut Self::UserState,
     ) -> Result<(), Self::Error> {
-        let config = serde_json::from_value::<RotationConfig>(data.clone())?;
-        Self::bind_perms(owner, config.y_rotation);
-        let mode = if config.y_rotation {
-            Mode::Facing
-        } else {
-            Mode::Cardinal
+        let geometry =
+            |components: &ComponentStore| Self::unknown(components, "minecraft:geometry").is_some();
+        let has_geometry = match config.target {
+            Target::Transformation => true,
+            Target::Bones => {
+                geometry(component_context)
+                    || owner.permutations.iter().any(|e| geometry(&e.components))
+            }
+            Target::State => geometry(component_context),
         };
+        if !has_geometry {
+            return Err(ComponentConfigError {
+                owner: owner.description.identifier.clone(),
+                permutation: addon.permutation().map(|e| e.index),
+                component: self.id().to_string(),
+                source: serde::de::Error::custom(
+                    "the bones and state targets need a minecraft:geometry",
+                ),
+            });
+        }
 
-        let add = Trait::PlacementDirection(PlacementDirection {
-            enabled_states: vec![mode.to_string()],
-            y_rotation_offset: 180,
-        });
-
-        owner
-            .description
-            .traits
-            .insert("minecraft:placement_direction".to_string(), add);
+        Self::bind_perms(owner, component_context, &config);
+        Self::bind_trait(owner, &config);
+        if state.placers {
+            Self::bind_placers(owner, &config, addon);
+        }
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Rotation;
-    use rusted_shut::addon::addon::Addon;
-    use rusted_shut::addon::blocks::block::Block;
-    use rusted_shut::addon::component::FormattedComponentRegister;
-    use rusted_shut::addon::custom_infrastructure::addon_processor::AddonProcessor;
-    use rusted_shut::addon::custom_infrastructure::component::custom_block::EmptyBlockState;
-    use rusted_shut::addon::path_resolver::default_impl::BaseResolver;
+    use crate::{Rotation, RotationState};
+    use rusted_shut::addon::custom_infrastructure::addon_processor::{
+        AddonProcessor, ProcessingError,
+    };
+    use rusted_shut::addon::custom_infrastructure::component::typed::{
+        ComponentConfigError, Typed,
+    };
     use rusted_shut::addon::traits::FormattedJsonSerialize;
+    use rusted_shut::test_utils::{addon, block_from};
     use serde_json::json;
 
+    /// Runs the rotation over the `minecraft:block` given as `block`, returning it afterwards
+    fn rotate(
+        block: serde_json::Value,
+    ) -> Result<serde_json::Value, ProcessingError<ComponentConfigError, ComponentConfigError>>
+    {
+        let id = block["description"]["identifier"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let addon = addon([block_from(block)]);
+
+        let mut process =
+            AddonProcessor::<ComponentConfigError, ComponentConfigError, RotationState>::new(
+                RotationState::default(),
+            );
+        process.bind_block_component(Typed(Rotation));
+        let addon = process.process_addon(addon)?;
+        Ok(addon.blocks_ref()[&id].to_json()["minecraft:block"].clone())
+    }
+
     #[test]
-    fn rotation_test() -> Result<(), serde_json::Error> {
-        let rot = Rotation;
-        let json = json!({
-          "format_version": "1.21.40",
-          "minecraft:block": {
-            "description": {
-              "identifier": "azur:sea_shells",
-              "menu_category": {
-                "category": "none",
-                "is_hidden_in_commands": true
-              },
-              "states": {
-                "azur:sea_shell_state": [0, 1, 2, 3]
-              }
+    fn rotation_test() {
+        let shells = rotate(json!({
+          "description": {
+            "identifier": "azur:sea_shells",
+            "menu_category": {
+              "category": "none",
+              "is_hidden_in_commands": true
             },
-            "components": {
-              "minecraft:material_instances": {
-                "*": {
-                  "texture": "azur:sea_shells_texture",
-                  "render_method": "alpha_test_single_sided"
-                }
-              },
-              "minecraft:geometry": {
-                "identifier": "geometry.azur.seashells",
-                "bone_visibility": {
-                  "2": "q.block_state('azur:sea_shell_state') >= 1",
-                  "3": "q.block_state('azur:sea_shell_state') >= 2",
-                  "4": "q.block_state('azur:sea_shell_state') == 3"
-                }
-              },
-              "azur:rotation": {},
+            "states": {
+              "azur:sea_shell_state": [0, 1, 2, 3]
             }
+          },
+          "components": {
+            "minecraft:material_instances": {
+              "*": {
+                "texture": "azur:sea_shells_texture",
+                "render_method": "alpha_test_single_sided"
+              }
+            },
+            "minecraft:geometry": {
+              "identifier": "geometry.azur.seashells",
+              "bone_visibility": {
+                "2": "q.block_state('azur:sea_shell_state') >= 1",
+                "3": "q.block_state('azur:sea_shell_state') >= 2",
+                "4": "q.block_state('azur:sea_shell_state') == 3"
+              }
+            },
+            "azur:rotation": {},
           }
-        });
-
-        let blk = Block::from_json(
-            &json,
-            &FormattedComponentRegister::new(),
-            semver::Version::new(0, 0, 0),
-        )
+        }))
         .unwrap();
+        assert_eq!(shells["permutations"].as_array().unwrap().len(), 4);
+    }
 
-        let mut addon = Addon::new(BaseResolver::new("./".into()));
-        addon.push_block(blk);
+    #[test]
+    fn axis_rotation() {
+        let log = rotate(
+            json!({"description":{"identifier":"azur:log"},"components":{"azur:rotation":{"mode":"axis"}}}),
+        ).unwrap();
+        assert_eq!(
+            log["description"]["traits"],
+            json!({"minecraft:placement_position":{"enabled_states":["minecraft:block_face"]}})
+        );
+        assert_eq!(
+            log["permutations"],
+            json!([
+                {"condition":"q.block_state('minecraft:block_face') == 'up' || q.block_state('minecraft:block_face') == 'down'","components":{"minecraft:transformation":{"rotation":[0,0,0]}}},
+                {"condition":"q.block_state('minecraft:block_face') == 'north' || q.block_state('minecraft:block_face') == 'south'","components":{"minecraft:transformation":{"rotation":[90,0,0]}}},
+                {"condition":"q.block_state('minecraft:block_face') == 'east' || q.block_state('minecraft:block_face') == 'west'","components":{"minecraft:transformation":{"rotation":[0,0,90]}}}
+            ])
+        );
+    }
 
-        let mut process =
-            AddonProcessor::<serde_json::Error, serde_json::Error, EmptyBlockState>::new(
-                EmptyBlockState,
-            );
-        process.bind_block_component(rot);
+    #[test]
+    fn crosses_existing_permutations() {
+        let door = rotate(
+            json!({"description":{"identifier":"azur:door"},"components":{"azur:rotation":{},"minecraft:transformation":{"scale":[1,1,0.5]}},"permutations":[
+                {"condition":"q.block_state('azur:open')","components":{"minecraft:transformation":{"rotation":[0,90,0],"translation":[0,0,1]}}},
+                {"condition":"q.block_state('azur:lit')","components":{"minecraft:light_emission":15}}
+            ]}),
+        ).unwrap();
+        let permutations = door["permutations"].as_array().unwrap();
+        assert_eq!(permutations.len(), 9);
 
-        let _ = process.process_addon(addon).unwrap();
-        Ok(())
+        // The block's own scale is kept by the rotation
+        assert_eq!(
+            permutations[1],
+            json!({"condition":"q.block_state('minecraft:cardinal_direction') == 'west'","components":{"minecraft:transformation":{"rotation":[0,90,0],"scale":[1,1,0.5]}}})
+        );
+        // The open door is turned further, with its offset turned along
+        assert_eq!(
+            permutations[5],
+            json!({"condition":"(q.block_state('azur:open')) && (q.block_state('minecraft:cardinal_direction') == 'west')","components":{"minecraft:transformation":{"rotation":[0,180,0],"translation":[1,0,0]}}})
+        );
+        assert_eq!(
+            permutations[8],
+            json!({"condition":"q.block_state('azur:lit')","components":{"minecraft:light_emission":15}})
+        );
+    }
+
+    #[test]
+    fn vertical_half_flip() {
+        let stair = rotate(
+            json!({"description":{"identifier":"azur:stair"},"components":{"azur:rotation":{"vertical_half":true}}}),
+        ).unwrap();
+        assert_eq!(
+            stair["description"]["traits"],
+            json!({
+                "minecraft:placement_direction":{"enabled_states":["minecraft:cardinal_direction"],"y_rotation_offset":180},
+                "minecraft:placement_position":{"enabled_states":["minecraft:vertical_half"]}
+            })
+        );
+
+        let permutations = stair["permutations"].as_array().unwrap();
+        assert_eq!(permutations.len(), 8);
+        assert_eq!(
+            permutations[2],
+            json!({"condition":"(q.block_state('minecraft:cardinal_direction') == 'west') && (q.block_state('minecraft:vertical_half') == 'bottom')","components":{"minecraft:transformation":{"rotation":[0,90,0]}}})
+        );
+        assert_eq!(
+            permutations[3],
+            json!({"condition":"(q.block_state('minecraft:cardinal_direction') == 'west') && (q.block_state('minecraft:vertical_half') == 'top')","components":{"minecraft:transformation":{"rotation":[180,90,0]}}})
+        );
+    }
+
+    #[test]
+    fn configured_rotation() {
+        // A model authored facing east, with its own south rotation
+        let lamp = rotate(
+            json!({"description":{"identifier":"azur:lamp"},"components":{"azur:rotation":{"offset":[0,90,0],"y_rotation_offset":0,"directions":{"south":[0,0,0]}}}}),
+        ).unwrap();
+        assert_eq!(
+            lamp["description"]["traits"],
+            json!({"minecraft:placement_direction":{"enabled_states":["minecraft:cardinal_direction"],"y_rotation_offset":0}})
+        );
+        assert_eq!(
+            lamp["permutations"][1]["components"],
+            json!({"minecraft:transformation":{"rotation":[0,180,0]}})
+        );
+        assert_eq!(
+            lamp["permutations"][2]["components"],
+            json!({"minecraft:transformation":{"rotation":[0,90,0]}})
+        );
+
+        let lamp = rotate(
+            json!({"description":{"identifier":"azur:lamp"},"components":{"azur:rotation":{"target":"bones"},"minecraft:geometry":"geometry.lamp"}}),
+        ).unwrap();
+        assert_eq!(
+            lamp["permutations"][1]["components"],
+            json!({"minecraft:geometry":{"identifier":"geometry.lamp","bone_visibility":{"north":false,"west":true,"south":false,"east":false}}})
+        );
+
+        let lamp = rotate(
+            json!({"description":{"identifier":"azur:lamp"},"components":{"azur:rotation":{"target":"state"},"minecraft:geometry":{"identifier":"geometry.lamp","bone_visibility":{"base":true}}}}),
+        ).unwrap();
+        assert_eq!(lamp["permutations"], json!([]));
+        assert_eq!(
+            lamp["components"]["minecraft:geometry"]["bone_visibility"],
+            json!({
+                "base":true,
+                "north":"q.block_state('minecraft:cardinal_direction') == 'north'",
+                "west":"q.block_state('minecraft:cardinal_direction') == 'west'",
+                "south":"q.block_state('minecraft:cardinal_direction') == 'south'",
+                "east":"q.block_state('minecraft:cardinal_direction') == 'east'"
+            })
+        );
+    }
+
+    #[test]
+    fn state_rotates_boxes() {
+        let shelf = rotate(
+            json!({"description":{"identifier":"azur:shelf"},"components":{"azur:rotation":{"target":"state"},"minecraft:geometry":"geometry.shelf","minecraft:collision_box":{"origin":[-8,4,-8],"size":[16,4,6]}},"permutations":[
+                {"condition":"q.block_state('azur:full')","components":{"minecraft:selection_box":{"origin":[-8,0,-8],"size":[16,16,6]}}},
+                {"condition":"q.block_state('azur:lit')","components":{"minecraft:light_emission":15}}
+            ]}),
+        ).unwrap();
+        let permutations = shelf["permutations"].as_array().unwrap();
+        assert_eq!(permutations.len(), 9);
+
+        // The geometry is left to molang, only the boxes turn
+        assert_eq!(
+            permutations[2],
+            json!({"condition":"q.block_state('minecraft:cardinal_direction') == 'south'","components":{"minecraft:collision_box":{"origin":[-8,4,2],"size":[16,4,6]}}})
+        );
+        assert_eq!(
+            permutations[6],
+            json!({"condition":"(q.block_state('azur:full')) && (q.block_state('minecraft:cardinal_direction') == 'south')","components":{"minecraft:collision_box":{"origin":[-8,4,2],"size":[16,4,6]},"minecraft:selection_box":{"origin":[-8,0,2],"size":[16,16,6]}}})
+        );
+        assert_eq!(
+            permutations[8],
+            json!({"condition":"q.block_state('azur:lit')","components":{"minecraft:light_emission":15}})
+        );
+    }
+
+    #[test]
+    fn existing_placement_direction() {
+        let lamp = rotate(
+            json!({"description":{"identifier":"azur:lamp","traits":{"minecraft:placement_direction":{"enabled_states":["minecraft:facing_direction"],"y_rotation_offset":90}}},"components":{"azur:rotation":{"y_rotation_offset":0}}}),
+        ).unwrap();
+        assert_eq!(
+            lamp["description"]["traits"],
+            json!({"minecraft:placement_direction":{"enabled_states":["minecraft:facing_direction","minecraft:cardinal_direction"],"y_rotation_offset":0}})
+        );
+
+        // Without a configured offset, the block's own is kept
+        let lamp = rotate(
+            json!({"description":{"identifier":"azur:lamp","traits":{"minecraft:placement_direction":{"enabled_states":[],"y_rotation_offset":90}}},"components":{"azur:rotation":{}}}),
+        ).unwrap();
+        assert_eq!(
+            lamp["description"]["traits"]["minecraft:placement_direction"]["y_rotation_offset"],
+            json!(90)
+        );
+    }
+
+    #[test]
+    fn bones_need_geometry() {
+        for target in ["bones", "state"] {
+            let result = rotate(
+                json!({"description":{"identifier":"azur:lamp"},"components":{"azur:rotation":{"target":target}}}),
+            );
+            assert!(matches!(
+                result,
+                Err(ProcessingError::BlockError(e)) if e.component == "azur:rotation"
+            ));
+        }
     }
 }
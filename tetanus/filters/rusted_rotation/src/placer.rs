@@ -0,0 +1,198 @@
+use crate::RotationState;
+use rusted_shut::addon::component::UnknownComponent;
+use rusted_shut::addon::component_store::ComponentStore;
+use rusted_shut::addon::custom_infrastructure::addon_context::AddonContext;
+use rusted_shut::addon::custom_infrastructure::component::custom_base::CustomComponent;
+use rusted_shut::addon::custom_infrastructure::component::typed::{
+    ComponentConfigError, TypedItemComponent,
+};
+use rusted_shut::addon::items::item::Item;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::any::Any;
+
+/// Makes an item placing a block turned by [`crate::Rotation`] stand in for the block's own item,
+/// which is drawn turned like the block. [`crate::Rotation`] adds it to the placing items.
+#[derive(Clone)]
+pub struct RotationPlacer;
+
+#[derive(Serialize, Deserialize)]
+pub struct RotationPlacerConfig {
+    pub block: String,
+    /// The `icon` of the block's rotation
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+impl CustomComponent for RotationPlacer {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn id(&self) -> &str {
+        Self::static_id()
+    }
+
+    fn static_id() -> &'static str
+    where
+        Self: Sized,
+    {
+        "azur:rotation_placer"
+    }
+}
+
+impl TypedItemComponent for RotationPlacer {
+    type Config = RotationPlacerConfig;
+    type UserState = RotationState;
+    type Error = ComponentConfigError;
+
+    fn apply(
+        &mut self,
+        config: Self::Config,
+        owner: &mut Item,
+        component_context: &mut ComponentStore,
+        _: &mut AddonContext,
+        _: &mut Self::UserState,
+    ) -> Result<(), Self::Error> {
+        if let Some(placer) =
+            component_context.get_component_mut::<UnknownComponent>("minecraft:block_placer")
+        {
+            if let Some(placer) = placer.data.as_object_mut() {
+                placer.insert("replace_block_item".to_string(), json!(true));
+            }
+        }
+
+        let has_icon = component_context.ids().any(|e| e == "minecraft:icon");
+        match config.icon {
+            Some(icon) => {
+                let icon = if owner.is_legacy() {
+                    json!({ "texture": icon })
+                } else {
+                    json!(icon)
+                };
+                component_context.set_component_box(
+                    Box::new(UnknownComponent::new(icon, "minecraft:icon".to_string())),
+                    "minecraft:icon".to_string(),
+                );
+            }
+            None if !has_icon => log::warn!(
+                "{} has no icon, so it shows {} turned. Set an icon on its rotation.",
+                owner.description.identifier,
+                config.block
+            ),
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::placer::RotationPlacer;
+    use crate::{Rotation, RotationState};
+    use rusted_shut::addon::custom_infrastructure::addon_processor::AddonProcessor;
+    use rusted_shut::addon::custom_infrastructure::component::typed::{
+        ComponentConfigError, Typed,
+    };
+    use rusted_shut::addon::traits::FormattedJsonSerialize;
+    use rusted_shut::parser::addon_parser::{AddonParser, ParserConfig};
+    use rusted_shut::test_utils::{addon, block, item};
+    use rusted_shut::vfs::memory::MemoryFileSystem;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn matches_placers() {
+        let mut addon = addon([
+            block(
+                "azur:chair",
+                json!({"azur:rotation":{"icon":"azur_chair_front"}}),
+            ),
+            block("azur:table", json!({"azur:rotation":{}})),
+            block("azur:rug", json!({})),
+        ]);
+        addon.push_item(item(
+            "azur:chair",
+            json!({"minecraft:block_placer":{"block":"azur:chair"}}),
+        ));
+        addon.push_item(item(
+            "azur:table",
+            json!({"minecraft:icon":"azur_table","minecraft:block_placer":{"block":{"name":"azur:table"}}}),
+        ));
+        addon.push_item(item(
+            "azur:rug",
+            json!({"minecraft:block_placer":{"block":"azur:rug"}}),
+        ));
+
+        let mut process =
+            AddonProcessor::<ComponentConfigError, ComponentConfigError, RotationState>::new(
+                RotationState { placers: true },
+            );
+        process
+            .bind_block_component(Typed(Rotation))
+            .bind_item_component(Typed(RotationPlacer));
+        let addon = process.process_addon(addon).unwrap();
+        let components =
+            |id: &str| addon.items_ref()[id].to_json()["minecraft:item"]["components"].clone();
+
+        // Gets the icon of the block's rotation
+        assert_eq!(
+            components("azur:chair"),
+            json!({"minecraft:icon":"azur_chair_front","minecraft:block_placer":{"block":"azur:chair","replace_block_item":true}})
+        );
+        // Keeps its own icon
+        assert_eq!(
+            components("azur:table"),
+            json!({"minecraft:icon":"azur_table","minecraft:block_placer":{"block":{"name":"azur:table"},"replace_block_item":true}})
+        );
+        // Its block isn't rotated
+        assert_eq!(
+            components("azur:rug"),
+            json!({"minecraft:block_placer":{"block":"azur:rug"}})
+        );
+    }
+
+    #[test]
+    fn parsed_placers() -> Result<(), Box<dyn std::error::Error>> {
+        let fs = MemoryFileSystem::new();
+        fs.insert(
+            "BP/blocks/chair.json",
+            json!({"format_version":"1.21.40","minecraft:block":{"description":{"identifier":"azur:chair"},"components":{"azur:rotation":{"icon":"azur_chair_front"}}}}).to_string(),
+        );
+        // Only vanilla components, yet kept for placing the chair
+        fs.insert(
+            "BP/items/chair.json",
+            json!({"format_version":"1.21.40","minecraft:item":{"description":{"identifier":"azur:chair"},"components":{"minecraft:block_placer":{"block":"azur:chair"}}}}).to_string(),
+        );
+
+        let addon = AddonParser::parse_addon_with(
+            fs.clone(),
+            "",
+            ParserConfig::builder()
+                .parse_block(true)
+                .parse_items(true)
+                .skip_bland(true)
+                .build(),
+        )?;
+        let mut process =
+            AddonProcessor::<ComponentConfigError, ComponentConfigError, RotationState>::new(
+                RotationState { placers: true },
+            );
+        process
+            .bind_block_component(Typed(Rotation))
+            .bind_item_component(Typed(RotationPlacer));
+        process.process_addon(addon)?.write()?;
+
+        let item: Value = serde_json::from_slice(&fs.get("BP/items/chair.json").unwrap())?;
+        assert_eq!(
+            item["minecraft:item"]["components"],
+            json!({"minecraft:icon":"azur_chair_front","minecraft:block_placer":{"block":"azur:chair","replace_block_item":true}})
+        );
+
+        Ok(())
+    }
+}
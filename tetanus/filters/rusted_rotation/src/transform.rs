@@ -0,0 +1,227 @@
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+type Matrix = [[f64; 3]; 3];
+
+/// Center of a block in the pixel coordinates of its boxes
+const BLOCK_CENTER: [f64; 3] = [0.0, 8.0, 0.0];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+fn apply(m: &Matrix, v: [f64; 3]) -> [f64; 3] {
+    [0, 1, 2].map(|i| (0..3).map(|k| m[i][k] * v[k]).sum())
+}
+
+/// Rotation matrix of euler angles in degrees, applied x first, then y, then z
+pub fn matrix(rotation: [f64; 3]) -> Matrix {
+    let [x, y, z] = rotation.map(f64::to_radians);
+    let (sx, cx) = x.sin_cos();
+    let (sy, cy) = y.sin_cos();
+    let (sz, cz) = z.sin_cos();
+
+    let rx = [[1.0, 0.0, 0.0], [0.0, cx, -sx], [0.0, sx, cx]];
+    let ry = [[cy, 0.0, sy], [0.0, 1.0, 0.0], [-sy, 0.0, cy]];
+    let rz = [[cz, -sz, 0.0], [sz, cz, 0.0], [0.0, 0.0, 1.0]];
+    multiply(&rz, &multiply(&ry, &rx))
+}
+
+/// Normalizes degrees into (-180, 180]
+fn wrap(degrees: f64) -> f64 {
+    let wrapped = degrees.rem_euclid(360.0);
+    if wrapped > 180.0 + 1e-9 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+/// The euler angles of `m`, see [`matrix`]. Of the two solutions, the one turning around fewer
+/// axes is picked.
+fn euler(m: &Matrix) -> [f64; 3] {
+    if m[2][0].abs() >= 1.0 - 1e-9 {
+        // Gimbal lock, all of the x/z rotation is put on x
+        let y = (-m[2][0]).clamp(-1.0, 1.0).asin();
+        let x = (-m[1][2]).atan2(m[1][1]);
+        return [x, y, 0.0].map(|e| wrap(clean(e.to_degrees())));
+    }
+
+    let y = (-m[2][0]).asin();
+    let first = [m[2][1].atan2(m[2][2]), y, m[1][0].atan2(m[0][0])];
+    let second = [
+        (-m[2][1]).atan2(-m[2][2]),
+        std::f64::consts::PI - y,
+        (-m[1][0]).atan2(-m[0][0]),
+    ];
+
+    let [first, second] = [first, second].map(|e| e.map(|e| wrap(clean(e.to_degrees()))));
+    let turns = |angles: &[f64; 3]| angles.iter().filter(|e| **e != 0.0).count();
+    if turns(&second) < turns(&first) {
+        second
+    } else {
+        first
+    }
+}
+
+/// The euler angles of turning by `inner`, then by `outer`
+pub fn compose(outer: [f64; 3], inner: [f64; 3]) -> [f64; 3] {
+    euler(&multiply(&matrix(outer), &matrix(inner)))
+}
+
+/// Rounds away floating point noise, so right angles stay whole numbers
+fn clean(v: f64) -> f64 {
+    let rounded = (v * 1e6).round() / 1e6;
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+fn vector_json(v: [f64; 3]) -> Value {
+    json!(v.map(|e| {
+        let e = clean(e);
+        if e.fract() == 0.0 {
+            json!(e as i64)
+        } else {
+            json!(e)
+        }
+    }))
+}
+
+/// Turns a `minecraft:collision_box` or `minecraft:selection_box` around the block's center,
+/// returning the box around the result. `None` if `json` isn't a box, e.g. `false`.
+pub fn rotate_box(json: &Value, rotation: [f64; 3]) -> Option<Value> {
+    let mut json = json.as_object()?.clone();
+    let origin = serde_json::from_value::<[f64; 3]>(json.get("origin")?.clone()).ok()?;
+    let size = serde_json::from_value::<[f64; 3]>(json.get("size")?.clone()).ok()?;
+
+    let m = matrix(rotation);
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+    for corner in 0..8 {
+        let point = [0, 1, 2].map(|i| {
+            let offset = if corner & (1 << i) == 0 { 0.0 } else { size[i] };
+            origin[i] + offset - BLOCK_CENTER[i]
+        });
+        let turned = apply(&m, point);
+        for i in 0..3 {
+            min[i] = min[i].min(turned[i] + BLOCK_CENTER[i]);
+            max[i] = max[i].max(turned[i] + BLOCK_CENTER[i]);
+        }
+    }
+
+    json.insert("origin".to_string(), vector_json(min));
+    json.insert(
+        "size".to_string(),
+        vector_json([0, 1, 2].map(|i| max[i] - min[i])),
+    );
+    Some(Value::Object(json))
+}
+
+/// The value of a `minecraft:transformation` component
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Transformation {
+    #[serde(default)]
+    pub translation: Option<[f64; 3]>,
+    #[serde(default)]
+    pub rotation: Option<[f64; 3]>,
+    #[serde(default)]
+    pub rotation_pivot: Option<[f64; 3]>,
+    #[serde(default)]
+    pub scale: Option<[f64; 3]>,
+    #[serde(default)]
+    pub scale_pivot: Option<[f64; 3]>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Transformation {
+    pub fn from_json(json: &Value) -> Option<Self> {
+        serde_json::from_value(json.clone()).ok()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut json = self.extra.clone();
+        let fields = [
+            ("translation", self.translation),
+            ("rotation", self.rotation),
+            ("rotation_pivot", self.rotation_pivot),
+            ("scale", self.scale),
+            ("scale_pivot", self.scale_pivot),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                json.insert(key.to_string(), vector_json(value));
+            }
+        }
+        Value::Object(json)
+    }
+
+    /// This transformation followed by turning the whole block by `rotation`. Scale and
+    /// pivots stay in the block's own space, translation is turned along.
+    pub fn rotated(&self, rotation: [f64; 3]) -> Self {
+        let outer = matrix(rotation);
+
+        // The outer rotation turns around the block's center, so an inner pivot off center
+        // moves the block
+        let pivot = self.rotation_pivot.unwrap_or_default();
+        let turned_pivot = apply(&outer, pivot);
+        let turned_translation = apply(&outer, self.translation.unwrap_or_default());
+        let translation = [0, 1, 2].map(|i| turned_translation[i] + turned_pivot[i] - pivot[i]);
+
+        Self {
+            translation: Some(translation).filter(|e| e.iter().any(|e| clean(*e) != 0.0)),
+            rotation: Some(compose(rotation, self.rotation.unwrap_or_default())),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transform::{rotate_box, Transformation};
+    use serde_json::json;
+
+    #[test]
+    fn composes_rotation() {
+        let base = Transformation::from_json(
+            &json!({"translation":[0,2,0],"rotation":[0,90,0],"scale":[0.5,1,0.5]}),
+        )
+        .unwrap();
+
+        // Turning a y rotated block onto its side keeps the scale, and moves the offset along
+        assert_eq!(
+            base.rotated([90.0, 0.0, 0.0]).to_json(),
+            json!({"translation":[0,0,2],"rotation":[90,0,90],"scale":[0.5,1,0.5]})
+        );
+        assert_eq!(
+            Transformation::default()
+                .rotated([0.0, -90.0, 0.0])
+                .to_json(),
+            json!({"rotation":[0,-90,0]})
+        );
+    }
+
+    #[test]
+    fn rotates_boxes() {
+        // A shelf hanging on the north wall
+        let shelf = json!({"origin":[-8,4,-8],"size":[16,4,6]});
+        assert_eq!(
+            rotate_box(&shelf, [0.0, 180.0, 0.0]),
+            Some(json!({"origin":[-8,4,2],"size":[16,4,6]}))
+        );
+        assert_eq!(
+            rotate_box(&shelf, [0.0, 90.0, 0.0]),
+            Some(json!({"origin":[-8,4,-8],"size":[6,4,16]}))
+        );
+        assert_eq!(rotate_box(&json!(false), [0.0, 90.0, 0.0]), None);
+    }
+}
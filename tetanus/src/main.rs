@@ -1,13 +1,12 @@
 #![allow(dead_code)]
-use rusted_office::config::{ExposedOfficeConfig, OfficeConfig};
-use rusted_office::filter::RustedOffice;
-use rusted_rotation::Rotation;
-use rusted_shut::addon::addon::Addon;
-use rusted_shut::addon::custom_infrastructure::addon_processor::AddonProcessor;
-use rusted_shut::addon::custom_infrastructure::component::custom_block::EmptyBlockState;
+use rusted_office::filter::OfficeFilter;
+use rusted_rotation::filter::RotationFilter;
 use rusted_shut::parser::addon_parser::{AddonParser, ParserConfig};
+use rusted_shut::pipeline::filter::FilterContext;
+use rusted_shut::pipeline::runner::{FilterEntry, FilterReport, Pipeline};
 use serde::Deserialize;
-use std::path::{Path, PathBuf};
+use serde_json::json;
+use std::path::PathBuf;
 
 fn default_rp() -> String {
     "RP".to_string()
@@ -45,11 +44,22 @@ struct TetanusConfig {
     #[serde(default = "true_func")]
     enable_rotation_filter: bool,
     #[serde(default = "false_func")]
+    load_bland_context: bool,
+    #[serde(default = "false_func")]
     enable_office: bool,
+    #[serde(default = "false_func")]
+    upgrade_legacy_items: bool,
+    /// Prints what would change instead of writing the addon
+    #[serde(default = "false_func")]
+    dry_run: bool,
     #[serde(default = "default_rp")]
     rp_path: String,
     #[serde(default = "default_bp")]
     bp_path: String,
+    #[serde(default)]
+    extra_rp_paths: Vec<String>,
+    #[serde(default)]
+    extra_bp_paths: Vec<String>,
     #[serde(default = "default_data")]
     data_path: String,
     #[serde(default = "default_script_path")]
@@ -58,17 +68,40 @@ struct TetanusConfig {
     base_path: String,
     #[serde(default = "default_main_file")]
     main_file: String,
+    #[serde(default)]
+    block_include: Vec<String>,
+    #[serde(default)]
+    block_exclude: Vec<String>,
+    #[serde(default)]
+    item_include: Vec<String>,
+    #[serde(default)]
+    item_exclude: Vec<String>,
+    /// The filters to run, in order. Without it `enable_rotation_filter` and `enable_office`
+    /// decide, running rotation before office.
+    filters: Option<Vec<FilterEntry>>,
+    /// Where to write the processing reports of the filters as JSON, relative to `base_path`
+    report_path: Option<String>,
+    /// Where a dry run writes its changes as JSON, relative to `base_path`
+    diff_path: Option<String>,
 }
 
 impl TetanusConfig {
-    pub fn real_script_dir(&self) -> Box<Path> {
-        let mut path = PathBuf::from(self.base_path.clone());
-        path.push(&self.script_path);
-        path.into_boxed_path()
-    }
-
-    pub fn real_main(&self) -> String {
-        format!("{}.{}", self.main_file, "ts")
+    pub fn filters(&self) -> Vec<FilterEntry> {
+        if let Some(filters) = &self.filters {
+            return filters.clone();
+        }
+
+        let mut filters = vec![];
+        if self.enable_rotation_filter {
+            filters.push(FilterEntry::new("rotation", json!({})));
+        }
+        if self.enable_office {
+            filters.push(FilterEntry::new(
+                "office",
+                json!({"script_path": self.script_path, "main_file": self.main_file}),
+            ));
+        }
+        filters
     }
 }
 
@@ -82,30 +115,33 @@ fn get_config() -> Result<TetanusConfig, serde_json::Error> {
     }
 }
 
-fn apply_rotation(addon: Addon) -> Addon {
-    let mut processor =
-        AddonProcessor::<serde_json::Error, serde_json::Error, EmptyBlockState>::new(
-            EmptyBlockState,
+fn print_reports(reports: &[FilterReport]) {
+    for FilterReport { filter, report } in reports {
+        println!(
+            "{filter}: {:.1}ms",
+            report.total_time().as_secs_f64() * 1000.0
         );
+        for (handler, entry) in &report.handlers {
+            println!(
+                "  {handler}: {} blocks, {} permutations, {} items, {:.1}ms",
+                entry.blocks.len(),
+                entry.permutations.len(),
+                entry.items.len(),
+                entry.time.as_secs_f64() * 1000.0
+            );
+        }
+    }
 
-    processor.bind_block_component(Rotation);
-
-    processor
-        .process_addon(addon)
-        .expect("Failed to apply rotations!")
-}
-
-fn apply_office(addon: Addon, config: &TetanusConfig) -> Addon {
-    let config = OfficeConfig {
-        parsed_config: ExposedOfficeConfig::default(),
-        script_entry: config.real_main(),
-        script_search_location: config.real_script_dir(),
+    // Every processor sees what the previous filters left, so the last one has the full picture
+    let Some(FilterReport { report, .. }) = reports.last() else {
+        return;
     };
-
-    let filter = RustedOffice::new(config);
-    let addon = filter.process(addon).expect("Failed to apply office!");
-
-    addon
+    for (key, owners) in &report.unhandled {
+        println!(
+            "Unhandled {key} on {}",
+            owners.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
 }
 
 fn main() {
@@ -115,30 +151,57 @@ fn main() {
         conf.base_path.clone(),
         ParserConfig::builder()
             .skip_bland(true)
+            .bland_as_read_only(conf.load_bland_context)
             .parse_items(true)
             .parse_block(true)
             .bp_from_base(conf.bp_path.clone())
             .rp_from_base(conf.rp_path.clone())
+            .extra_bp_from_base(conf.extra_bp_paths.clone())
+            .extra_rp_from_base(conf.extra_rp_paths.clone())
+            .block_include(conf.block_include.clone())
+            .block_exclude(conf.block_exclude.clone())
+            .item_include(conf.item_include.clone())
+            .item_exclude(conf.item_exclude.clone())
             .build(),
     )
     .expect(&format!("Failed to parse addon: Config: {conf:?}"));
 
-    if conf.enable_rotation_filter {
-        addon = apply_rotation(addon);
+    let mut pipeline = Pipeline::new(FilterContext {
+        base_path: PathBuf::from(&conf.base_path),
+    });
+    pipeline
+        .register::<RotationFilter>()
+        .register::<OfficeFilter>();
+
+    let reports;
+    (addon, reports) = pipeline
+        .run_reported(addon, &conf.filters())
+        .unwrap_or_else(|e| panic!("Failed to run filters: {e}"));
+
+    print_reports(&reports);
+    if let Some(report_path) = &conf.report_path {
+        let mut pth = PathBuf::from(&conf.base_path);
+        pth.push(report_path);
+        std::fs::write(pth, serde_json::to_string_pretty(&reports).unwrap())
+            .expect("Failed to write the processing report");
     }
 
-    if conf.enable_office {
-        let old_wd = std::env::current_dir().unwrap();
-        std::env::set_current_dir(
-            Path::new(&conf.base_path)
-                .canonicalize()
-                .expect("Base path does not exist!"),
-        )
-        .expect("Failed to change into the addon directory");
-        addon = apply_office(addon, &conf);
-
-        std::env::set_current_dir(&old_wd).expect("Failed to change into the old directory");
+    if conf.upgrade_legacy_items {
+        addon.upgrade_legacy_items();
     }
 
-    addon.write().unwrap()
+    if !conf.dry_run {
+        return addon.write().unwrap();
+    }
+
+    let diffs = addon.dry_run().expect("Failed to diff the addon");
+    for diff in &diffs {
+        print!("{}", diff.unified);
+    }
+    if let Some(diff_path) = &conf.diff_path {
+        let mut pth = PathBuf::from(&conf.base_path);
+        pth.push(diff_path);
+        std::fs::write(pth, serde_json::to_string_pretty(&diffs).unwrap())
+            .expect("Failed to write the diff");
+    }
 }